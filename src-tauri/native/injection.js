@@ -127,6 +127,66 @@
         };
     }
 
+    /**
+     * Invokes an exit/restart command. In kiosk mode Rust requires the admin
+     * PIN from the policy file, so the user is prompted for it first. Without
+     * a PIN only a --kiosk launch refuses these commands.
+     */
+    async function invokeWithKioskPin(command) {
+        const kiosk = await invoke('kiosk_status');
+        let pin = null;
+        if (kiosk.active && kiosk.pinConfigured) {
+            pin = window.prompt('Enter the administrator PIN');
+            if (pin === null) return;
+        } else if (kiosk.active && kiosk.forced) {
+            console.warn('[JellyfinTauri] Kiosk mode active and no admin PIN configured — ' + command + ' refused');
+            return;
+        }
+        return invoke(command, { pin });
+    }
+
+    /**
+     * Turning main.forceAlwaysFS off while it holds kiosk mode on also needs
+     * the admin PIN. Resolves to the PIN, or null when none is needed.
+     */
+    async function kioskPinForSettings(turnsKioskOff) {
+        if (!turnsKioskOff) return null;
+        const kiosk = await invoke('kiosk_status');
+        if (!kiosk.active || !kiosk.pinConfigured) return null;
+        return window.prompt('Enter the administrator PIN');
+    }
+
+    const turnsKioskOff = (section, key, value) =>
+        section === 'main' && key === 'forceAlwaysFS' && value !== true;
+
+    // Rust downloads cover art with the session's token; pass it along
+    // whenever it changes (sign-in, sign-out, server switch)
+    let lastAccessToken;
+    function syncAccessToken() {
+        let token = null;
+        try {
+            token = window.ApiClient?.accessToken?.() || null;
+        } catch (_) {}
+        if (token === lastAccessToken) return;
+        lastAccessToken = token;
+        invoke('media_set_access_token', { token }).catch(() => {});
+    }
+
+    // Fields the OS media controls show for a jellyfin item
+    function describeMediaItem(item, serverUrl) {
+        return {
+            title: item.Name || '',
+            artist: item.ArtistItems?.[0]?.Name || item.SeriesName || '',
+            album: item.Album || '',
+            coverUrl: item.Id
+                ? `${serverUrl}/Items/${item.Id}/Images/Primary?maxHeight=300`
+                : null,
+            durationMs: item.RunTimeTicks
+                ? Math.round(item.RunTimeTicks / 10000)
+                : null,
+        };
+    }
+
     // ========================================================================
     // API Shim — replaces window.api created by QWebChannel
     // ========================================================================
@@ -137,17 +197,11 @@
         player: {
             // Outbound notifications (JS → Rust) — update OS media controls (SMTC/MPRIS)
             notifyMetadata: (item, serverUrl) => {
-                const title = item.Name || '';
-                const artist = item.ArtistItems?.[0]?.Name || item.SeriesName || '';
-                const album = item.Album || '';
-                const coverUrl = item.Id
-                    ? `${serverUrl}/Items/${item.Id}/Images/Primary?maxHeight=300`
-                    : null;
-                const durationMs = item.RunTimeTicks
-                    ? Math.round(item.RunTimeTicks / 10000)
-                    : null;
+                syncAccessToken();
                 return invoke('media_notify_metadata', {
-                    title, artist, album, coverUrl, durationMs,
+                    ...describeMediaItem(item, serverUrl),
+                    // 'Video' or 'Audio' — selects the autopause settings
+                    mediaType: item.MediaType || null,
                 }).catch(() => {});
             },
             notifyPosition: (ms) => {
@@ -191,11 +245,31 @@
                     canPrev: !!canPrev,
                 }).catch(() => {});
             },
+            // Play queue for the MPRIS TrackList; entries are keyed by
+            // PlaylistItemId, which media-goto hands back
+            notifyTrackList: (items, currentId, serverUrl) => {
+                syncAccessToken();
+                // GoTo answers with the track id, which only means something
+                // to jellyfin-web as a PlaylistItemId; entries without one
+                // can't be addressed and are left out
+                const tracks = (items || [])
+                    .filter((item) => item && item.PlaylistItemId)
+                    .map((item) => ({
+                        id: String(item.PlaylistItemId),
+                        ...describeMediaItem(item, serverUrl),
+                    }));
+                invoke('media_notify_tracklist', {
+                    tracks,
+                    current: currentId || null,
+                }).catch(() => {});
+            },
+            // Deletes downloaded cover art; resolves to the bytes freed
+            clearCoverArtCache: () => invoke('media_clear_cover_cache'),
             notifyPlaybackStop: (isNavigating) => {
                 return invoke('media_notify_stop').catch(() => {});
             },
             notifySeek: (ms) => {
-                invoke('media_notify_position', {
+                invoke('media_notify_seek', {
                     positionMs: Math.round(ms),
                 }).catch(() => {});
             },
@@ -222,27 +296,53 @@
         },
 
         settings: {
-            allValues: (section)          => invoke('settings_get_all', { section }),
-            setValue:  (section, key, val) => invoke('settings_set_value', { section, key, value: val }),
+            allValues: (section)          => invoke('settings_get_all', { section }).then(r => r.values),
+            setValue:  async (section, key, val) => {
+                const pin = await kioskPinForSettings(turnsKioskOff(section, key, val));
+                return invoke('settings_set_value', { section, key, value: val, pin });
+            },
             value:     (section, key)      => invoke('settings_get_value', { section, key }),
             settingsValue: createSignal('settings-value-changed'),
+            // Fired once per setValues() batch: { changes: [{ section, key, oldValue, value }] }
+            settingsValues: createSignal('settings-values-changed'),
 
             // Batch write: options = { section: { key: value, ... }, ... }
-            setValues: (options) => invoke('settings_set_values', { values: options }),
+            setValues: async (options) => {
+                const main = options.main || {};
+                const pin = await kioskPinForSettings(
+                    'forceAlwaysFS' in main && turnsKioskOff('main', 'forceAlwaysFS', main.forceAlwaysFS));
+                return invoke('settings_set_values', { values: options, pin });
+            },
+
+            // Per-server overrides for the active server (video/audio/subtitles only)
+            setServerValue: (section, key, val) => invoke('settings_set_value', { section, key, value: val, scope: 'server' }),
+            clearServerValue: (section, key)     => invoke('settings_clear_override', { section, key }),
+            // Resolves to { value, source: 'server' | 'global' | 'default' | 'policy', serverId }
+            effectiveValue:  (section, key)      => invoke('settings_get_effective', { section, key }),
 
             // Reset a section to its defaults (from settingsDescriptions)
             resetToDefault: async (section) => {
                 const descriptions = window.jmpInfo.settingsDescriptions[section];
                 if (!descriptions) return;
+                // Resetting main turns forceAlwaysFS off; ask for the PIN once
+                const pin = await kioskPinForSettings(section === 'main');
                 // Delete persisted keys for this section
-                await invoke('settings_delete_section', { section });
-                // Restore in-memory settings to defaults
+                try {
+                    await invoke('settings_delete_section', { section, pin });
+                } catch (e) {
+                    console.warn(`[JellyfinTauri] Failed to reset ${section} settings:`, e);
+                }
+                // Restore in-memory settings to defaults (policy-locked and
+                // protected keys keep their value)
                 for (const [key, desc] of Object.entries(descriptions)) {
-                    if (desc.default !== undefined) {
-                        await invoke('settings_set_value', { section, key, value: desc.default });
+                    if (desc.default === undefined || !isSettingWritable(section, key)) continue;
+                    try {
+                        await invoke('settings_set_value', { section, key, value: desc.default, pin });
                         if (window.jmpInfo.settings[section]) {
                             window.jmpInfo.settings[section][key] = desc.default;
                         }
+                    } catch (e) {
+                        console.warn(`[JellyfinTauri] Failed to reset ${section}.${key}:`, e);
                     }
                 }
                 // Notify listeners
@@ -254,7 +354,11 @@
             // Reset all sections to defaults
             resetToDefaultAll: async () => {
                 for (const section of Object.keys(window.jmpInfo.settingsDescriptions)) {
-                    await window.api.settings.resetToDefault(section);
+                    try {
+                        await window.api.settings.resetToDefault(section);
+                    } catch (e) {
+                        console.warn(`[JellyfinTauri] Failed to reset ${section} settings:`, e);
+                    }
                 }
             },
 
@@ -269,8 +373,9 @@
         system: {
             hello:                   (name) => invoke('system_hello', { name }),
             openExternalUrl:         (url)  => invoke('system_open_external_url', { url }),
-            exit:                    ()     => invoke('system_exit'),
-            restart:                 ()     => invoke('system_restart'),
+            exit:                    ()     => invokeWithKioskPin('system_exit'),
+            restart:                 ()     => invokeWithKioskPin('system_restart'),
+            kioskStatus:             ()     => invoke('kiosk_status'),
             debugInformation:        ()     => invoke('system_debug_info'),
             checkForUpdates:         ()     => invoke('system_check_for_updates'),
             checkServerConnectivity: (url)  => invoke('check_server_connectivity', { url }),
@@ -300,29 +405,65 @@
             volumeChanged: createSignal('input-volume-changed'),
             rateChanged:   createSignal('input-rate-changed'),
             positionSeek:  createSignal('input-position-seek'),
+
+            // Keymap ({ bindings: [{ action, name, keys, defaults, locked }], conflicts: [{ keys, actions }] })
+            keymap:        ()               => invoke('input_get_keymap'),
+            // Resolves to the conflicts after the change
+            setBinding:    (action, keys)   => invoke('input_set_binding', { action, keys }),
+            // Omit action to reset every binding
+            resetBindings: (action)         => invoke('input_reset_bindings', { action }),
+            conflicts:     ()               => invoke('input_get_conflicts'),
+            keymapChanged: createSignal('input-keymap-changed'),
+        },
+
+        hotkeys: {
+            // [{ action, shortcut, registered, conflict }]
+            status:  () => invoke('hotkeys_get_status'),
+            changed: createSignal('hotkeys-changed'),
         },
 
         power: {
             setScreensaverEnabled: (enabled) => invoke('power_set_screensaver_enabled', { enabled }),
+            // Linux: before suspend, pause and report progress, then call suspendReady
+            suspending:   createSignal('system-suspending'),
+            resumed:      createSignal('system-resumed'),
+            suspendReady: ()        => invoke('power_suspend_ready'),
         },
 
-        display: {},
+        display: {
+            // [{ name, primary, current: { width, height, refreshRate }, modes: [...] }]
+            listOutputs:      ()    => invoke('display_list_outputs'),
+            matchRefreshRate: (fps) => invoke('display_match_refresh_rate', { fps }),
+            restore:          ()    => invoke('display_restore'),
+        },
 
         window: {
-            setFullscreen: (fs) => {
-                // Kiosk mode: block exiting fullscreen if forceAlwaysFS is enabled
-                if (!fs && window.jmpInfo?.settings?.main?.forceAlwaysFS) {
-                    console.log('[JellyfinTauri] Kiosk mode active — blocking fullscreen exit');
-                    return Promise.resolve();
-                }
-                return invoke('window_set_fullscreen', { fullscreen: fs });
-            },
+            // Kiosk mode is enforced in Rust, which refuses to leave fullscreen
+            setFullscreen: (fs) => invoke('window_set_fullscreen', { fullscreen: fs }).catch((e) => {
+                console.log('[JellyfinTauri] Fullscreen change refused:', e);
+            }),
             isFullscreen:    ()        => invoke('window_is_fullscreen'),
             setAlwaysOnTop:  (enabled) => invoke('window_set_always_on_top', { enabled }),
             isAlwaysOnTop:   ()        => invoke('window_is_always_on_top'),
             raise:           ()        => invoke('window_raise'),
             setCursorVisible:(visible) => invoke('window_set_cursor_visible', { visible }),
             saveGeometry:    ()        => invoke('window_save_geometry'),
+            // [{ name, x, y, width, height, scaleFactor, refreshRate, primary, current }]
+            listMonitors:    ()        => invoke('window_list_monitors'),
+            // aspect = video width / height (defaults to 16:9)
+            setPictureInPicture: (enabled, aspect) => invoke('window_set_picture_in_picture', { enabled, aspect }),
+            isPictureInPicture:  ()        => invoke('window_is_picture_in_picture'),
+            pictureInPictureChanged: createSignal('window-pip-changed'),
+            // 'desktop' or 'tv'; switching saves it and reloads the page
+            setLayoutMode:   (mode)    => invoke('window_set_layout_mode', { mode }),
+            getLayoutMode:   ()        => invoke('window_get_layout_mode'),
+            // Borderless window, remembered per layout
+            setCinemaMode:   (enabled) => invoke('window_set_cinema_mode', { enabled }),
+            isCinemaMode:    ()        => invoke('window_is_cinema_mode'),
+            cinemaModeChanged: createSignal('window-cinema-changed'),
+            // scale: '0.5', '1', '2' or 'fit'; needs a playing video
+            fitToVideo:      (scale)   => invoke('window_fit_to_video', { scale }),
+            setVideoSize:    (size)    => invoke('window_set_video_size', { size }),
             setTitle:        (title)   => invoke('window_set_title', { title }),
         },
     };
@@ -330,6 +471,33 @@
     // apiPromise resolves immediately — Tauri IPC is always available
     window.apiPromise = Promise.resolve(window.api);
 
+    // ========================================================================
+    // Cinema mode drag regions
+    // ========================================================================
+    // Without decorations the window moves by dragging the areas matched by
+    // main.cinemaDragRegions; double-clicking them toggles maximize. Controls
+    // inside those areas keep working.
+    const DRAG_EXCLUDE = 'button, a, input, select, textarea, [role="button"], [role="slider"], .emby-button';
+    let cinemaMode = false;
+
+    function installCinemaDragRegions() {
+        api.window.isCinemaMode().then((enabled) => { cinemaMode = enabled; }).catch(() => {});
+        api.window.cinemaModeChanged.connect((enabled) => { cinemaMode = enabled; });
+
+        document.addEventListener('mousedown', (e) => {
+            if (!cinemaMode || e.button !== 0 || !(e.target instanceof Element)) return;
+            const selector = window.jmpInfo.settings.main.cinemaDragRegions;
+            if (!selector || e.target.closest(DRAG_EXCLUDE)) return;
+            try {
+                if (!e.target.closest(selector)) return;
+            } catch (err) {
+                return; // invalid selector in settings
+            }
+            e.preventDefault();
+            invoke(e.detail === 2 ? 'window_toggle_maximize' : 'window_start_drag').catch(() => {});
+        }, true);
+    }
+
     // ========================================================================
     // jmpInfo — version & settings info object
     // ========================================================================
@@ -345,98 +513,59 @@
             isMac: isMac,
             isLinux: isLinux,
         },
+        // Values and descriptions are filled in by AppHost.init() from the
+        // Rust settings schema (settings_schema / settings_get_all)
         settings: {
             main: {},
-            video: {
-                force_transcode_dovi:  false,
-                force_transcode_hdr:   false,
-                force_transcode_hi10p: false,
-                force_transcode_hevc:  false,
-                force_transcode_av1:   false,
-                force_transcode_4k:    false,
-                always_force_transcode: false,
-                max_audio_channels:    '6',
-                default_playback_speed: 1,
-                aspect: 'auto',
-            },
-            audio: {
-                device:             'auto',
-                channels:           'auto',
-                exclusive:          false,
-                normalize:          false,
-                passthrough_ac3:    false,
-                passthrough_dts:    false,
-                passthrough_eac3:   false,
-                passthrough_dtshd:  false,
-                passthrough_truehd: false,
-            },
-            subtitles: {
-                size:               '32',
-                font:               '',
-                color:              '#FFFFFFFF',
-                border_color:       '#FF000000',
-                border_size:        '2',
-                background_color:   '#00000000',
-                ass_style_override: 'yes',
-                ass_scale_border:   true,
-            },
-        },
-        settingsDescriptions: {
-            main: {
-                fullscreen:      { type: 'bool', default: false, name: 'Fullscreen' },
-                alwaysOnTop:     { type: 'bool', default: false, name: 'Always on Top' },
-                forceAlwaysFS:   { type: 'bool', default: false, name: 'Kiosk Mode (prevent exiting fullscreen)' },
-                allowBrowserZoom:{ type: 'bool', default: false, name: 'Allow Browser Zoom' },
-            },
-            video: {
-                force_transcode_dovi:  { type: 'bool', default: false, name: 'Force transcode Dolby Vision' },
-                force_transcode_hdr:   { type: 'bool', default: false, name: 'Force transcode HDR' },
-                force_transcode_hi10p: { type: 'bool', default: false, name: 'Force transcode 10-bit' },
-                force_transcode_hevc:  { type: 'bool', default: false, name: 'Force transcode HEVC/H.265' },
-                force_transcode_av1:   { type: 'bool', default: false, name: 'Force transcode AV1' },
-                force_transcode_4k:    { type: 'bool', default: false, name: 'Force transcode 4K' },
-                always_force_transcode: { type: 'bool', default: false, name: 'Always force transcoding' },
-                max_audio_channels:    { type: 'select', default: '6', name: 'Max audio channels', options: ['2', '6', '8'] },
-                default_playback_speed: { type: 'select', default: '1', name: 'Default playback speed', options: ['0.5', '0.75', '1', '1.25', '1.5', '1.75', '2'] },
-                hardwareDecoding:      { type: 'select', default: 'auto-safe', name: 'Hardware Decoding',
-                                         options: ['auto-safe', 'auto-copy', 'no'] },
-                deinterlace:           { type: 'bool', default: false, name: 'Deinterlace' },
-                sync_mode:             { type: 'select', default: 'audio', name: 'Video Sync Mode',
-                                         options: ['audio', 'display-resample', 'display-adrop'] },
-                cache_mb:              { type: 'select', default: '150', name: 'Cache Size (MB)',
-                                         options: ['10', '75', '150', '500'] },
-                audio_delay_ms:        { type: 'text', default: '0', name: 'Audio Delay (ms)' },
-                allow_transcode_to_hevc: { type: 'bool', default: false, name: 'Allow Transcode to HEVC' },
-            },
-            audio: {
-                device:             { type: 'select', default: 'auto', name: 'Audio Device', options: ['auto'] },
-                channels:           { type: 'select', default: 'auto', name: 'Audio Channels', options: ['auto', '2.0', '5.1', '7.1'] },
-                exclusive:          { type: 'bool', default: false, name: 'Exclusive Audio Mode (WASAPI)' },
-                normalize:          { type: 'bool', default: false, name: 'Normalize Downmix Volume' },
-                passthrough_ac3:    { type: 'bool', default: false, name: 'Passthrough AC3' },
-                passthrough_dts:    { type: 'bool', default: false, name: 'Passthrough DTS' },
-                passthrough_eac3:   { type: 'bool', default: false, name: 'Passthrough E-AC3' },
-                passthrough_dtshd:  { type: 'bool', default: false, name: 'Passthrough DTS-HD' },
-                passthrough_truehd: { type: 'bool', default: false, name: 'Passthrough TrueHD' },
-            },
-            subtitles: {
-                size:               { type: 'select', default: '32', name: 'Subtitle Size',
-                                      options: ['16', '20', '24', '28', '32', '36', '40', '48', '56', '64', '72'] },
-                font:               { type: 'text', default: '', name: 'Subtitle Font (blank = default)' },
-                color:              { type: 'text', default: '#FFFFFFFF', name: 'Subtitle Color (hex ARGB)' },
-                border_color:       { type: 'text', default: '#FF000000', name: 'Border Color (hex ARGB)' },
-                border_size:        { type: 'select', default: '2', name: 'Border Size',
-                                      options: ['0', '1', '2', '3', '4', '5', '6'] },
-                background_color:   { type: 'text', default: '#00000000', name: 'Background Color (hex ARGB)' },
-                ass_style_override: { type: 'select', default: 'yes', name: 'ASS Style Override',
-                                      options: ['yes', 'no', 'force', 'scale', 'strip'] },
-                ass_scale_border:   { type: 'bool', default: true, name: 'Scale ASS Border & Shadow' },
-            },
+            video: {},
+            audio: {},
+            subtitles: {},
         },
+        settingsDescriptions: {},
+        // Keys pinned by the admin policy file, per section
+        settingsLocked: {},
+        // Protected keys this page may not write (server pages), per section
+        settingsReadOnly: {},
         settingsUpdate: [],
         settingsDescriptionsUpdate: [],
     };
 
+    // ========================================================================
+    // Settings Schema — converts the Rust `settings_schema` section list into
+    // the { section: { key: { type, default, name, options } } } shape used by
+    // the settings modal and resetToDefault()
+    // ========================================================================
+    function buildSettingsDescriptions(schema) {
+        const descriptions = {};
+        for (const section of schema || []) {
+            descriptions[section.name] = {};
+            for (const { key, ...desc } of section.settings) {
+                descriptions[section.name][key] = desc;
+            }
+        }
+        return descriptions;
+    }
+
+    // Keys described by the Rust schema; jellyfin-web also puts its own
+    // props on the settings objects, which Rust would reject
+    function isKnownSetting(section, key) {
+        const descriptions = window.jmpInfo.settingsDescriptions[section];
+        return !!descriptions && Object.prototype.hasOwnProperty.call(descriptions, key);
+    }
+
+    function isSettingLocked(section, key) {
+        const locked = window.jmpInfo.settingsLocked[section];
+        return !!locked && locked.includes(key);
+    }
+
+    function isSettingReadOnly(section, key) {
+        const readOnly = window.jmpInfo.settingsReadOnly[section];
+        return !!readOnly && readOnly.includes(key);
+    }
+
+    const isSettingWritable = (section, key) =>
+        !isSettingLocked(section, key) && !isSettingReadOnly(section, key);
+
     // ========================================================================
     // Codec Profiles — dynamically built from user's force-transcode settings
     // Each enabled flag adds conditions that prevent direct play for that content
@@ -566,8 +695,16 @@
             panel.appendChild(heading);
 
             for (const [key, desc] of Object.entries(secDesc)) {
+                if (desc.hidden) continue;
                 const row = document.createElement('div');
                 row.style.cssText = 'display:flex;align-items:center;justify-content:space-between;padding:5px 0;';
+                const locked = !isSettingWritable(secName, key);
+                if (locked) {
+                    row.title = isSettingLocked(secName, key)
+                        ? 'Locked by administrator policy'
+                        : 'Can only be changed from the connect page';
+                    row.style.opacity = '0.6';
+                }
 
                 const lbl = document.createElement('label');
                 lbl.textContent = desc.name || key;
@@ -584,6 +721,7 @@
                         window.api.settings.setValue(secName, key, cb.checked).catch(() => {});
                         window.jmpInfo.settingsUpdate.forEach(fn => { try { fn(secName); } catch(e) {} });
                     });
+                    cb.disabled = locked;
                     row.appendChild(cb);
                 } else if (desc.type === 'select') {
                     const sel = document.createElement('select');
@@ -601,8 +739,44 @@
                         window.api.settings.setValue(secName, key, sel.value).catch(() => {});
                         window.jmpInfo.settingsUpdate.forEach(fn => { try { fn(secName); } catch(e) {} });
                     });
+                    sel.disabled = locked;
                     row.appendChild(sel);
-                } else if (desc.type === 'text') {
+                } else if (desc.type === 'number') {
+                    const inp = document.createElement('input');
+                    inp.type = 'number';
+                    inp.min = desc.min;
+                    inp.max = desc.max;
+                    inp.step = 1;
+                    inp.value = secSettings[key] != null ? String(secSettings[key]) : String(desc.default);
+                    inp.style.cssText = 'background:#333;color:#eee;border:1px solid #555;border-radius:4px;padding:4px 8px;font-size:0.9em;max-width:180px;width:100%;';
+                    inp.addEventListener('change', () => {
+                        const num = parseInt(inp.value, 10);
+                        if (Number.isNaN(num)) return;
+                        settings[secName][key] = num;
+                        window.api.settings.setValue(secName, key, num).catch(() => {});
+                        window.jmpInfo.settingsUpdate.forEach(fn => { try { fn(secName); } catch(e) {} });
+                    });
+                    inp.disabled = locked;
+                    row.appendChild(inp);
+                } else if (desc.type === 'keys') {
+                    // Edited as a comma-separated list ("Space, P, Ctrl+Q")
+                    const inp = document.createElement('input');
+                    inp.type = 'text';
+                    inp.value = (secSettings[key] || []).join(', ');
+                    inp.placeholder = (desc.default || []).join(', ');
+                    inp.style.cssText = 'background:#333;color:#eee;border:1px solid #555;border-radius:4px;padding:4px 8px;font-size:0.9em;max-width:180px;width:100%;';
+                    inp.addEventListener('change', () => {
+                        const keys = inp.value.split(',').map(k => k.trim()).filter(k => k);
+                        window.api.input.setBinding(key, keys).then(() => {
+                            settings[secName][key] = keys;
+                        }).catch((e) => {
+                            console.warn('[JellyfinTauri] Key binding rejected:', e);
+                            inp.value = (secSettings[key] || []).join(', ');
+                        });
+                    });
+                    inp.disabled = locked;
+                    row.appendChild(inp);
+                } else if (desc.type === 'text' || desc.type === 'color' || desc.type === 'shortcut') {
                     const inp = document.createElement('input');
                     inp.type = 'text';
                     inp.value = secSettings[key] != null ? String(secSettings[key]) : '';
@@ -613,6 +787,7 @@
                         window.api.settings.setValue(secName, key, inp.value).catch(() => {});
                         window.jmpInfo.settingsUpdate.forEach(fn => { try { fn(secName); } catch(e) {} });
                     });
+                    inp.disabled = locked;
                     row.appendChild(inp);
                 }
                 panel.appendChild(row);
@@ -657,7 +832,7 @@
         resetServerBtn.addEventListener('click', async () => {
             if (confirm('Clear saved server URL and restart?')) {
                 await invoke('save_server_url', { url: '' });
-                await invoke('system_restart');
+                await window.api.system.restart();
             }
         });
         btnRow.appendChild(resetServerBtn);
@@ -799,73 +974,36 @@
             init: async function () {
                 const api = await window.apiPromise;
 
-                // Load saved settings from Tauri store
+                // Load the settings schema (types, defaults, options) from Rust
                 try {
-                    const settings = await api.settings.allValues('main');
-                    window.jmpInfo.settings.main = settings || {};
+                    const schema = await invoke('settings_schema');
+                    window.jmpInfo.settingsDescriptions = buildSettingsDescriptions(schema);
                 } catch (e) {
-                    console.warn('[JellyfinTauri] Failed to load settings:', e);
-                    window.jmpInfo.settings.main = {};
+                    console.warn('[JellyfinTauri] Failed to load settings schema:', e);
                 }
 
-                // Load video settings
-                try {
-                    const videoSettings = await api.settings.allValues('video');
-                    if (videoSettings && typeof videoSettings === 'object') {
-                        // Merge saved values over defaults (keep defaults for any missing keys)
-                        for (const [k, v] of Object.entries(videoSettings)) {
-                            window.jmpInfo.settings.video[k] = v;
-                        }
-                    }
-                } catch (e) {
-                    console.warn('[JellyfinTauri] Failed to load video settings:', e);
-                }
-
-                // Load audio settings
-                try {
-                    const audioSettings = await api.settings.allValues('audio');
-                    if (audioSettings && typeof audioSettings === 'object') {
-                        for (const [k, v] of Object.entries(audioSettings)) {
-                            window.jmpInfo.settings.audio[k] = v;
-                        }
+                // Load saved settings — Rust merges persisted values over schema
+                // defaults and reports which keys the admin policy locks
+                for (const section of Object.keys(window.jmpInfo.settingsDescriptions)) {
+                    try {
+                        const { values, locked, readOnly } = await invoke('settings_get_all', { section });
+                        window.jmpInfo.settings[section] = (values && typeof values === 'object') ? values : {};
+                        window.jmpInfo.settingsLocked[section] = locked || [];
+                        window.jmpInfo.settingsReadOnly[section] = readOnly || [];
+                    } catch (e) {
+                        console.warn(`[JellyfinTauri] Failed to load ${section} settings:`, e);
+                        window.jmpInfo.settings[section] = window.jmpInfo.settings[section] || {};
                     }
-                } catch (e) {
-                    console.warn('[JellyfinTauri] Failed to load audio settings:', e);
                 }
 
-                // Load subtitle settings
+                // Rust restores each layout's window state at startup and on
+                // layout switches; reflect it here rather than re-applying
                 try {
-                    const subtitleSettings = await api.settings.allValues('subtitles');
-                    if (subtitleSettings && typeof subtitleSettings === 'object') {
-                        for (const [k, v] of Object.entries(subtitleSettings)) {
-                            window.jmpInfo.settings.subtitles[k] = v;
-                        }
-                    }
+                    window.jmpInfo.mode = await api.window.getLayoutMode();
+                    window.jmpInfo.settings.main.fullscreen = await api.window.isFullscreen();
+                    window.jmpInfo.settings.main.alwaysOnTop = await api.window.isAlwaysOnTop();
                 } catch (e) {
-                    console.warn('[JellyfinTauri] Failed to load subtitle settings:', e);
-                }
-
-                // Apply defaults for missing settings
-                const defaults = {
-                    fullscreen: false,
-                    alwaysOnTop: false,
-                    forceAlwaysFS: false,
-                    allowBrowserZoom: false,
-                };
-                for (const [k, v] of Object.entries(defaults)) {
-                    if (window.jmpInfo.settings.main[k] === undefined) {
-                        window.jmpInfo.settings.main[k] = v;
-                    }
-                }
-
-                // Apply fullscreen if saved
-                if (window.jmpInfo.settings.main.fullscreen) {
-                    api.window.setFullscreen(true);
-                }
-
-                // Apply always-on-top if saved
-                if (window.jmpInfo.settings.main.alwaysOnTop) {
-                    api.window.setAlwaysOnTop(true);
+                    console.warn('[JellyfinTauri] Failed to read window state:', e);
                 }
 
                 // Zoom control — block Ctrl+Scroll and Ctrl+Plus/Minus unless allowed
@@ -887,11 +1025,26 @@
                     document.addEventListener('DOMContentLoaded', applyZoomLock);
                 }
 
-                // Settings proxy — saves to store and notifies listeners
+                // Persist a proxy write. Props outside the schema stay in
+                // memory only, and a refused write is logged, not thrown.
+                const persistSetting = (section, prop, value) => {
+                    if (!isKnownSetting(section, prop)) return;
+                    api.settings.setValue(section, prop, value).catch((e) => {
+                        console.warn(`[JellyfinTauri] Failed to save ${section}.${prop}:`, e);
+                    });
+                };
+
+                // Settings proxy — saves to store and notifies listeners.
+                // fullscreen/alwaysOnTop only mirror the window state, which
+                // Rust saves per layout, so they are not written back.
+                const windowStateMirrors = ['fullscreen', 'alwaysOnTop'];
                 const settingsHandler = {
                     set(target, prop, value) {
+                        if (!isSettingWritable('main', prop)) return true;
                         target[prop] = value;
-                        api.settings.setValue('main', prop, value);
+                        if (!windowStateMirrors.includes(prop)) {
+                            persistSetting('main', prop, value);
+                        }
                         window.jmpInfo.settingsUpdate.forEach(fn => {
                             try { fn('main'); } catch (e) { console.error(e); }
                         });
@@ -906,8 +1059,9 @@
                 // Video settings proxy — same pattern
                 const videoSettingsHandler = {
                     set(target, prop, value) {
+                        if (!isSettingWritable('video', prop)) return true;
                         target[prop] = value;
-                        api.settings.setValue('video', prop, value);
+                        persistSetting('video', prop, value);
                         window.jmpInfo.settingsUpdate.forEach(fn => {
                             try { fn('video'); } catch (e) { console.error(e); }
                         });
@@ -922,8 +1076,9 @@
                 // Audio settings proxy — same pattern
                 const audioSettingsHandler = {
                     set(target, prop, value) {
+                        if (!isSettingWritable('audio', prop)) return true;
                         target[prop] = value;
-                        api.settings.setValue('audio', prop, value);
+                        persistSetting('audio', prop, value);
                         window.jmpInfo.settingsUpdate.forEach(fn => {
                             try { fn('audio'); } catch (e) { console.error(e); }
                         });
@@ -938,8 +1093,9 @@
                 // Subtitle settings proxy — same pattern
                 const subtitleSettingsHandler = {
                     set(target, prop, value) {
+                        if (!isSettingWritable('subtitles', prop)) return true;
                         target[prop] = value;
-                        api.settings.setValue('subtitles', prop, value);
+                        persistSetting('subtitles', prop, value);
                         window.jmpInfo.settingsUpdate.forEach(fn => {
                             try { fn('subtitles'); } catch (e) { console.error(e); }
                         });
@@ -989,13 +1145,14 @@
                     }
                 };
                 startObserving();
+                installCinemaDragRegions();
 
                 api.system.hello('jellyfin-desktop-tauri');
                 console.log('[JellyfinTauri] NativeShell initialized');
             },
 
             getDefaultLayout: function () {
-                return 'desktop';
+                return window.jmpInfo.mode;
             },
 
             supports: function (command) {
@@ -1039,7 +1196,7 @@
             },
 
             exit: function () {
-                invoke('system_exit');
+                window.api.system.exit();
             },
 
             getPlugins: function () {
@@ -33,71 +33,36 @@
         'mute':                 'togglemute',
     };
 
+    // Keymap actions handled by the host rather than jellyfin-web
+    const hostActionMap = {
+        'fullscreen':           'host:fullscreen',
+        'toggle_debug':         'host:toggleDebug',
+        'close':                'host:close',
+    };
+
     // ====================================================================
-    // Key Map: keyboard key values → native action names
+    // Key Map: owned by Rust (`input` settings section), loaded through
+    // api.input.keymap() and reloaded on api.input.keymapChanged
     // ====================================================================
-    const keyMap = {
-        // Playback
-        ' ':                'play_pause',
-        'p':                'play_pause',
-        'P':                'play_pause',
-        'x':                'stop',
-        'X':                'stop',
-
-        // Audio / Subtitles
-        'a':                'cycle_audio',
-        'A':                'cycle_audio',
-        'l':                'cycle_subtitles',
-        'L':                'cycle_subtitles',
-        's':                'toggle_subtitles',
-        'S':                'toggle_subtitles',
-
-        // Volume
-        '+':                'increase_volume',
-        '=':                'increase_volume',
-        '-':                'decrease_volume',
-
-        // Seeking
-        'PageUp':           'seek_backward',
-        'PageDown':         'seek_forward',
-        'Home':             'step_backward',
-        'End':              'step_forward',
-
-        // Navigation
-        'F11':              'host:fullscreen',
-        'Escape':           'back',
-        'Backspace':        'back',
-        'Enter':            'enter',
-
-        // Advanced
-        'z':                'cycle_aspect',
-        'Z':                'cycle_aspect',
-        'i':                'host:toggleDebug',
-        'I':                'host:toggleDebug',
-
-        // Browser media keys
-        'MediaPlayPause':   'play_pause',
-        'MediaTrackNext':   'next_track',
-        'MediaTrackPrevious': 'previous_track',
-        'MediaStop':        'stop',
-    };
 
-    // Ctrl+ combos
-    const ctrlKeyMap = {
-        'a':                'cycle_audio_back',
-        'A':                'cycle_audio_back',
-        'l':                'cycle_subtitles_back',
-        'L':                'cycle_subtitles_back',
-        'q':                'host:close',
-        'Q':                'host:close',
-        'w':                'host:close',
-        'W':                'host:close',
-    };
+    // Canonical Rust combination ("Ctrl+Shift+P") → lookup key ("ctrl+shift+p")
+    function comboId(combo) {
+        return combo.toLowerCase();
+    }
 
-    // Alt+ combos
-    const altKeyMap = {
-        'Enter':            'host:fullscreen',
-    };
+    // Lookup key for a keydown event, optionally with Shift
+    function eventComboId(e, withShift) {
+        let key = e.key;
+        if (key === ' ') key = 'Space';
+        else if (key === '+') key = 'Plus';
+        return comboId(
+            (e.ctrlKey ? 'Ctrl+' : '') +
+            (e.altKey ? 'Alt+' : '') +
+            (withShift ? 'Shift+' : '') +
+            (e.metaKey ? 'Meta+' : '') +
+            key
+        );
+    }
 
     // Tags where keyboard shortcuts should NOT fire
     const INPUT_TAGS = new Set(['INPUT', 'TEXTAREA', 'SELECT']);
@@ -122,6 +87,8 @@
             this._positionInterval = null;
             this._lastPositionMs = 0;
             this._boundKeyHandler = this._onKeyDown.bind(this);
+            this._boundKeymapHandler = this._applyKeymap.bind(this);
+            this._keyBindings = new Map();
             this._boundEventHandlers = {};
 
             this._setupNativeInputSignal();
@@ -149,6 +116,33 @@
         // ================================================================
         _setupKeyboardShortcuts() {
             document.addEventListener('keydown', this._boundKeyHandler, true);
+
+            const api = window.api;
+            if (api && api.input && api.input.keymap) {
+                api.input.keymap().then(this._boundKeymapHandler).catch((e) => {
+                    console.warn('[InputPlugin] Failed to load keymap:', e);
+                });
+                api.input.keymapChanged.connect(this._boundKeymapHandler);
+            }
+        }
+
+        // keymap = { bindings: [{ action, keys }], conflicts: [{ keys, actions }] }
+        _applyKeymap(keymap) {
+            const bindings = new Map();
+            for (const binding of keymap.bindings || []) {
+                const action = hostActionMap[binding.action] || binding.action;
+                for (const combo of binding.keys || []) {
+                    // First action wins on a conflict, like the schema order
+                    if (!bindings.has(comboId(combo))) {
+                        bindings.set(comboId(combo), action);
+                    }
+                }
+            }
+            for (const conflict of keymap.conflicts || []) {
+                console.warn('[InputPlugin] Key binding conflict:', conflict.keys, conflict.actions);
+            }
+            this._keyBindings = bindings;
+            console.log('[InputPlugin] Keymap loaded:', bindings.size, 'bindings');
         }
 
         _onKeyDown(e) {
@@ -161,14 +155,11 @@
                 if (active.closest && active.closest('[contenteditable="true"]')) return;
             }
 
-            let action = null;
-
-            if (e.ctrlKey && !e.altKey && !e.metaKey) {
-                action = ctrlKeyMap[e.key];
-            } else if (e.altKey && !e.ctrlKey && !e.metaKey) {
-                action = altKeyMap[e.key];
-            } else if (!e.ctrlKey && !e.altKey && !e.metaKey) {
-                action = keyMap[e.key];
+            // A binding that names Shift wins; otherwise Shift is ignored so
+            // that "P" also fires with Shift or Caps Lock held
+            let action = e.shiftKey ? this._keyBindings.get(eventComboId(e, true)) : undefined;
+            if (!action) {
+                action = this._keyBindings.get(eventComboId(e, false));
             }
 
             if (!action) return;
@@ -286,6 +277,74 @@
                     console.warn('[InputPlugin] SetVolume failed:', e);
                 }
             });
+
+            // SetRate: OS requests playback speed (payload = rate, 1.0 = normal)
+            this._unlistenMediaSetRate = listen('media-set-rate', (event) => {
+                const rate = event.payload;
+                console.log('[InputPlugin] OS media set-rate:', rate);
+                const pm = this._playbackManager;
+                try {
+                    const player = pm.getCurrentPlayer();
+                    if (player) {
+                        pm.setPlaybackRate(rate, player);
+                    }
+                } catch (e) {
+                    console.warn('[InputPlugin] SetRate failed:', e);
+                }
+            });
+
+            // SetShuffle: OS toggles queue shuffle (payload = bool)
+            this._unlistenMediaSetShuffle = listen('media-set-shuffle', (event) => {
+                const enabled = !!event.payload;
+                console.log('[InputPlugin] OS media set-shuffle:', enabled);
+                try {
+                    this._playbackManager.setQueueShuffleMode(enabled ? 'Shuffle' : 'Sorted');
+                } catch (e) {
+                    console.warn('[InputPlugin] SetShuffle failed:', e);
+                }
+            });
+
+            // SetRepeat: OS changes loop status (payload = jellyfin repeat mode)
+            this._unlistenMediaSetRepeat = listen('media-set-repeat', (event) => {
+                const mode = event.payload;
+                console.log('[InputPlugin] OS media set-repeat:', mode);
+                try {
+                    this._playbackManager.setRepeatMode(mode);
+                } catch (e) {
+                    console.warn('[InputPlugin] SetRepeat failed:', e);
+                }
+            });
+
+            // GoTo: OS picks a queue entry (payload = PlaylistItemId)
+            this._unlistenMediaGoTo = listen('media-goto', (event) => {
+                const playlistItemId = event.payload;
+                console.log('[InputPlugin] OS media go-to:', playlistItemId);
+                const pm = this._playbackManager;
+                try {
+                    pm.setCurrentPlaylistItem(playlistItemId, pm.getCurrentPlayer());
+                } catch (e) {
+                    console.warn('[InputPlugin] GoTo failed:', e);
+                }
+            });
+
+            // System suspend: pausing makes jellyfin-web report progress; Rust
+            // holds the suspend until suspendReady (or its own timeout)
+            this._unlistenSystemSuspending = listen('system-suspending', () => {
+                console.log('[InputPlugin] System suspending');
+                const pm = this._playbackManager;
+                try {
+                    const player = pm.getCurrentPlayer();
+                    if (player && !pm.paused(player)) {
+                        pm.pause(player);
+                    }
+                } catch (e) {
+                    console.warn('[InputPlugin] Pause before suspend failed:', e);
+                }
+                // Give the progress report a moment to reach the server
+                setTimeout(() => {
+                    window.api.power.suspendReady().catch(() => {});
+                }, 1000);
+            });
         }
 
         // ================================================================
@@ -410,6 +469,8 @@
                     }, retryDelay);
                 }
 
+                this._notifyQueue();
+
                 // Start position update interval
                 this._startPositionInterval();
             });
@@ -465,7 +526,27 @@
                     const enabled = pm.getQueueShuffleMode ? pm.getQueueShuffleMode() !== 'Sorted' : false;
                     api.player.notifyShuffleChange(enabled);
                 } catch (_) {}
+                this._notifyQueue();
             });
+
+            // --- queue edits ---
+            on('playlistitemadd', () => this._notifyQueue());
+            on('playlistitemremove', () => this._notifyQueue());
+            on('playlistitemmove', () => this._notifyQueue());
+        }
+
+        // Play queue and CanGoNext/CanGoPrevious for OS media controls
+        _notifyQueue() {
+            const pm = this._playbackManager;
+            try {
+                Promise.resolve(pm.getPlaylist()).then((items) => {
+                    const index = pm.getCurrentPlaylistIndex();
+                    const length = items ? items.length : 0;
+                    window.api.player.notifyQueueChange(index >= 0 && index < length - 1, index > 0);
+                    const currentId = pm.getCurrentPlaylistItemId ? pm.getCurrentPlaylistItemId() : null;
+                    window.api.player.notifyTrackList(items, currentId, this._getServerUrl());
+                }).catch(() => {});
+            } catch (_) {}
         }
 
         // ================================================================
@@ -531,6 +612,9 @@
         // ================================================================
         destroy() {
             document.removeEventListener('keydown', this._boundKeyHandler, true);
+            if (window.api && window.api.input && window.api.input.keymapChanged) {
+                window.api.input.keymapChanged.disconnect(this._boundKeymapHandler);
+            }
             this._stopPositionInterval();
 
             // Re-enable screensaver on cleanup
@@ -555,6 +639,26 @@
                 this._unlistenMediaSetVolume.then(fn => fn());
                 this._unlistenMediaSetVolume = null;
             }
+            if (this._unlistenMediaSetRate) {
+                this._unlistenMediaSetRate.then(fn => fn());
+                this._unlistenMediaSetRate = null;
+            }
+            if (this._unlistenMediaSetShuffle) {
+                this._unlistenMediaSetShuffle.then(fn => fn());
+                this._unlistenMediaSetShuffle = null;
+            }
+            if (this._unlistenMediaSetRepeat) {
+                this._unlistenMediaSetRepeat.then(fn => fn());
+                this._unlistenMediaSetRepeat = null;
+            }
+            if (this._unlistenMediaGoTo) {
+                this._unlistenMediaGoTo.then(fn => fn());
+                this._unlistenMediaGoTo = null;
+            }
+            if (this._unlistenSystemSuspending) {
+                this._unlistenSystemSuspending.then(fn => fn());
+                this._unlistenSystemSuspending = null;
+            }
 
             // Unsubscribe from playback events
             const events = this._events;
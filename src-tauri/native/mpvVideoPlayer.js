@@ -79,6 +79,8 @@
                                 'demuxer-cache-duration': 'double', // buffered seconds ahead
                                 'seeking': 'flag',       // true while a seek is in progress
                                 'idle-active': 'flag',   // true when mpv is idle (no file)
+                                'video-params/dw': 'int64', // display size, for fitting the window
+                                'video-params/dh': 'int64',
                             },
                         };
 
@@ -269,6 +271,7 @@
             this.isFetching = false;
 
             this._videoDialog = undefined;
+            this._pictureInPicture = false;
             this._subtitleTrackIndexToSetOnPlaying = undefined;
             this._audioTrackIndexToSetOnPlaying = undefined;
             this._showTrackOffset = undefined;
@@ -293,6 +296,10 @@
             this._trackMap = new Map();    // jellyfinIndex -> mpvTrackId
             this._isTransitioning = false; // true between loadfile and file-loaded
             this._startedAt = 0;           // timestamp when _started became true
+            this._videoSize = { width: 0, height: 0 }; // mpv video-params/dw, dh
+            this._videoSizeReported = false; // sent to Rust once per file
+            this._suspendState = null;     // { positionMs, subtitleUrl } noted before system suspend
+            this._reconnect = null;        // _suspendState while the stream reloads after resume
 
             // ================================================================
             // Event handlers (bound to this instance)
@@ -434,6 +441,7 @@
                         case 'speed':
                             if (mpvEvent.data != null) {
                                 this._playRate = mpvEvent.data;
+                                window.api.player.notifyRateChange(mpvEvent.data);
                             }
                             break;
                         case 'volume':
@@ -470,10 +478,23 @@
                         case 'aid':
                             console.log('[MPV] Active audio track changed to:', mpvEvent.data);
                             break;
+                        case 'video-params/dw':
+                            this._videoSize.width = Number(mpvEvent.data) || 0;
+                            this._reportVideoSize();
+                            break;
+                        case 'video-params/dh':
+                            this._videoSize.height = Number(mpvEvent.data) || 0;
+                            this._reportVideoSize();
+                            break;
                     }
                 } else if (mpvEvent.event === 'file-loaded') {
-                    // File loaded, playback starting
-                    this.onPlaying();
+                    if (this._reconnect) {
+                        // Reloaded after resume: still paused, nothing to announce
+                        this._finishReconnect();
+                    } else {
+                        // File loaded, playback starting
+                        this.onPlaying();
+                    }
                 } else if (mpvEvent.event === 'playback-restart') {
                     // Seek completed, playback resumed
                     if (this._seeking) {
@@ -501,6 +522,82 @@
                     });
                 }
             });
+
+            // Rust leaves picture-in-picture on its own when going fullscreen
+            window.api.window.pictureInPictureChanged.connect((enabled) => {
+                self._pictureInPicture = enabled;
+            });
+
+            // System suspend (Linux): the stream is reloaded after wake-up
+            window.api.power.suspending.connect(() => self._onSystemSuspending());
+            window.api.power.resumed.connect(() => {
+                self._reconnectAfterResume();
+            });
+        }
+
+        /**
+         * Note where playback was before system suspend. The server side of
+         * the stream times out while asleep, so it is reloaded from here.
+         */
+        _onSystemSuspending() {
+            if (!this._currentSrc || this._currentTime == null) return;
+            const sub = this._mpvTrackList.find(t => t.type === 'sub' && t.selected && t.external);
+            this._suspendState = {
+                positionMs: this._currentTime,
+                subtitleUrl: sub ? sub['external-filename'] : null,
+            };
+        }
+
+        /**
+         * Reload the stream at the position saved before suspend, paused and
+         * with the same audio and subtitle tracks.
+         */
+        async _reconnectAfterResume() {
+            const saved = this._suspendState;
+            this._suspendState = null;
+            if (!saved || !this._currentSrc) return;
+            console.log('[MPV] Reconnecting stream after resume at', saved.positionMs, 'ms');
+
+            const aid = await mpv.getProperty('aid', 'string').catch(() => null);
+            // External subtitles are added again once the file has loaded
+            const sid = saved.subtitleUrl ? null : await mpv.getProperty('sid', 'string').catch(() => null);
+            const options = [`start=${saved.positionMs / 1000}`, 'pause=yes'];
+            if (aid) options.push(`aid=${aid}`);
+            if (sid) options.push(`sid=${sid}`);
+
+            this._reconnect = saved;
+            this._isTransitioning = true;
+            try {
+                await mpv.command('loadfile', [this._currentSrc, 'replace', options.join(',')]);
+            } catch (e) {
+                console.warn('[MPV] Failed to reconnect after resume:', e);
+                this._reconnect = null;
+                this._isTransitioning = false;
+            }
+        }
+
+        _finishReconnect() {
+            const { subtitleUrl } = this._reconnect;
+            this._reconnect = null;
+            this._isTransitioning = false;
+            if (subtitleUrl) {
+                mpv.command('sub-add', [subtitleUrl, 'select']).catch(e => {
+                    console.warn('[MPV] Failed to restore external subtitle:', e);
+                });
+            }
+        }
+
+        /**
+         * Send the video's display size to Rust, which fits the window to it
+         * when video.auto_fit_window is set. Once per file.
+         */
+        _reportVideoSize() {
+            const { width, height } = this._videoSize;
+            if (this._videoSizeReported || !width || !height) return;
+            this._videoSizeReported = true;
+            window.api.window.setVideoSize({ width, height }).catch((e) => {
+                console.warn('[MPV] Failed to report video size:', e);
+            });
         }
 
         /**
@@ -943,6 +1040,9 @@
             // Reset vid to auto in case audio player set it to 'no'
             await mpv.setProperty('vid', 'auto');
 
+            // Report the new video's size once mpv knows it (window fitting)
+            this._videoSizeReported = false;
+
             // Apply audio configuration settings (device, passthrough, channels, etc.)
             try {
                 await this._applyAudioSettings();
@@ -964,6 +1064,19 @@
                 console.warn('[MPV] Failed to apply video settings:', e);
             }
 
+            // Switch the display to a refresh rate matching the video before
+            // the file starts; restored in stop()
+            if (window.jmpInfo?.settings?.video?.refresh_rate_switching) {
+                const fps = this.tryGetFramerate(options);
+                if (fps) {
+                    try {
+                        await window.api.display.matchRefreshRate(fps);
+                    } catch (e) {
+                        console.warn('[MPV] Failed to match display refresh rate:', e);
+                    }
+                }
+            }
+
             // Stash pending track selections — applied after file-loaded event
             this._pendingSubtitleSetup = null;
             this._pendingAudioSetup = null;
@@ -1202,6 +1315,9 @@
             this._isTransitioning = false; // cancel any pending transition
             mpv.command('stop').catch(() => {});
             this.onEndedInternal();
+            window.api.display.restore().catch(() => {});
+            this._videoSizeReported = false;
+            window.api.window.setVideoSize(null).catch(() => {});
 
             if (destroyPlayer) {
                 this.destroy();
@@ -1223,6 +1339,9 @@
         }
 
         destroy() {
+            if (this._pictureInPicture) {
+                this.setPictureInPictureEnabled(false);
+            }
             this.removeMediaDialog();
 
             this._hasConnection = false;
@@ -1318,7 +1437,7 @@
         }
 
         static getSupportedFeatures() {
-            return ['PlaybackRate', 'SetAspectRatio', 'SubtitleOffset', 'SetAudioStreamIndex'];
+            return ['PlaybackRate', 'SetAspectRatio', 'SubtitleOffset', 'SetAudioStreamIndex', 'PictureInPicture'];
         }
 
         supports(feature) {
@@ -1372,8 +1491,22 @@
             return true;
         }
 
-        setPictureInPictureEnabled() {}
-        isPictureInPictureEnabled() { return false; }
+        async setPictureInPictureEnabled(isEnabled) {
+            // Size the mini player to the video; Rust falls back to 16:9
+            const aspect = isEnabled
+                ? await mpv.getProperty('video-params/aspect', 'double').catch(() => null)
+                : null;
+            try {
+                await window.api.window.setPictureInPicture(!!isEnabled, aspect);
+                this._pictureInPicture = !!isEnabled;
+            } catch (e) {
+                console.warn('[MPV] Picture-in-picture change refused:', e);
+            }
+        }
+
+        isPictureInPictureEnabled() {
+            return this._pictureInPicture;
+        }
         isAirPlayEnabled() { return false; }
         setAirPlayEnabled() {}
         setBrightness() {}
@@ -1472,7 +1605,9 @@
             return this._muted;
         }
 
-        togglePictureInPicture() {}
+        togglePictureInPicture() {
+            return this.setPictureInPictureEnabled(!this.isPictureInPictureEnabled());
+        }
         toggleAirPlay() {}
 
         getBufferedRanges() {
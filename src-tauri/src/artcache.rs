@@ -0,0 +1,263 @@
+// ========================================================================
+// Cover Art Cache
+// ========================================================================
+//
+// On-disk cache of cover images for the OS media controls. SMTC and MPRIS
+// clients fetch artwork themselves, without the session's token, so the app
+// downloads covers and hands them a `file://` URL instead.
+//
+// Each URL is stored as one file named by a stable hash of the URL, with
+// the extension taken from its content type. A file's modification time
+// records when it was last used; once the directory grows past the size
+// limit the least recently used files are removed first.
+
+use log::{debug, warn};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Extensions written by `store`, checked in order by `lookup`
+const EXTENSIONS: &[&str] = &["jpg", "png", "webp", "gif", "bmp"];
+
+// Largest single image accepted, whatever the cache size; covers are well
+// under a megabyte
+const MAX_IMAGE_BYTES: u64 = 8 * 1024 * 1024;
+
+// File extension for an image content type; None if it isn't an image
+pub fn extension_for(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    match mime.to_ascii_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        "image/gif" => Some("gif"),
+        "image/bmp" => Some("bmp"),
+        _ => None,
+    }
+}
+
+// 64-bit FNV-1a, so file names stay the same across builds
+fn cache_key(url: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in url.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+pub struct ArtCache {
+    dir: PathBuf,
+    limit_bytes: u64,
+}
+
+impl ArtCache {
+    pub fn new(dir: PathBuf, limit_bytes: u64) -> ArtCache {
+        ArtCache { dir, limit_bytes }
+    }
+
+    // Cached file for `url`, marked as just used
+    pub fn lookup(&self, url: &str) -> Option<PathBuf> {
+        let key = cache_key(url);
+        let path = EXTENSIONS
+            .iter()
+            .map(|ext| self.dir.join(format!("{}.{}", key, ext)))
+            .find(|path| path.is_file())?;
+        touch(&path);
+        Some(path)
+    }
+
+    // Largest image `store` accepts: an eighth of the cache, at most 8 MiB,
+    // so one download cannot flush the rest of the cache
+    pub fn max_image_bytes(&self) -> u64 {
+        (self.limit_bytes / 8).min(MAX_IMAGE_BYTES)
+    }
+
+    // Write the image for `url`, then evict down to the size limit
+    pub fn store(&self, url: &str, extension: &str, bytes: &[u8]) -> io::Result<PathBuf> {
+        if bytes.len() as u64 > self.max_image_bytes() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "image is too large for the cache",
+            ));
+        }
+        fs::create_dir_all(&self.dir)?;
+        let key = cache_key(url);
+        let path = self.dir.join(format!("{}.{}", key, extension));
+        // Write under a temporary name so readers never see a partial file
+        let partial = self.dir.join(format!("{}.part", key));
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, &path)?;
+        self.evict()?;
+        Ok(path)
+    }
+
+    // Remove least recently used files until the cache fits the limit
+    pub fn evict(&self) -> io::Result<()> {
+        let mut files = self.files()?;
+        let mut total: u64 = files.iter().map(|f| f.size).sum();
+        if total <= self.limit_bytes {
+            return Ok(());
+        }
+        files.sort_by_key(|f| f.used);
+        for file in files {
+            if total <= self.limit_bytes {
+                break;
+            }
+            match fs::remove_file(&file.path) {
+                Ok(()) => total -= file.size,
+                Err(e) => warn!("Failed to evict {}: {}", file.path.display(), e),
+            }
+        }
+        Ok(())
+    }
+
+    // Remove every cached file; returns the number of bytes freed
+    pub fn clear(&self) -> io::Result<u64> {
+        let mut freed = 0;
+        for file in self.files()? {
+            fs::remove_file(&file.path)?;
+            freed += file.size;
+        }
+        Ok(freed)
+    }
+
+    fn files(&self) -> io::Result<Vec<CachedFile>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            files.push(CachedFile {
+                path: entry.path(),
+                size: metadata.len(),
+                used: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+        Ok(files)
+    }
+}
+
+struct CachedFile {
+    path: PathBuf,
+    size: u64,
+    used: SystemTime,
+}
+
+// Bump the modification time, which orders eviction
+fn touch(path: &Path) {
+    let result = fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.set_modified(SystemTime::now()));
+    if let Err(e) = result {
+        debug!("Failed to touch {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // A cache directory of its own under the system temp dir, removed on drop
+    struct TempCache {
+        cache: ArtCache,
+    }
+
+    impl TempCache {
+        fn new(name: &str, limit_bytes: u64) -> TempCache {
+            let dir = std::env::temp_dir().join(format!("artcache-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            TempCache {
+                cache: ArtCache::new(dir, limit_bytes),
+            }
+        }
+
+        fn total(&self) -> u64 {
+            self.cache.files().unwrap().iter().map(|f| f.size).sum()
+        }
+
+        // Store `size` bytes for `url`, last used `age` seconds ago
+        fn store_aged(&self, url: &str, size: usize, age: u64) -> PathBuf {
+            let path = self.cache.store(url, "jpg", &vec![0; size]).unwrap();
+            let used = SystemTime::now() - Duration::from_secs(age);
+            fs::File::options().write(true).open(&path).unwrap().set_modified(used).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempCache {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.cache.dir);
+        }
+    }
+
+    #[test]
+    fn stored_images_are_found_by_url() {
+        let temp = TempCache::new("lookup", 8000);
+        assert_eq!(temp.cache.lookup("http://server/a"), None);
+        let path = temp.cache.store("http://server/a", "png", &[1, 2, 3]).unwrap();
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("png"));
+        assert_eq!(temp.cache.lookup("http://server/a"), Some(path));
+        assert_eq!(temp.cache.lookup("http://server/b"), None);
+    }
+
+    #[test]
+    fn store_keeps_the_total_under_the_limit() {
+        let temp = TempCache::new("limit", 8000);
+        for i in 0..20 {
+            temp.store_aged(&format!("http://server/{}", i), 1000, 20 - i);
+            assert!(temp.total() <= 8000, "{} bytes after {} stores", temp.total(), i + 1);
+        }
+        assert_eq!(temp.total(), 8000);
+    }
+
+    #[test]
+    fn eviction_removes_the_least_recently_used_first() {
+        let temp = TempCache::new("lru", 24_000);
+        let oldest = temp.store_aged("http://server/oldest", 3000, 300);
+        let newest = temp.store_aged("http://server/newest", 3000, 100);
+        let middle = temp.store_aged("http://server/middle", 3000, 200);
+        // A lookup counts as a use
+        temp.store_aged("http://server/looked-up", 3000, 400);
+        assert!(temp.cache.lookup("http://server/looked-up").is_some());
+
+        let cache = ArtCache::new(temp.cache.dir.clone(), 7000);
+        cache.evict().unwrap();
+        assert!(!oldest.exists());
+        assert!(!middle.exists());
+        assert!(newest.exists());
+        assert!(cache.lookup("http://server/looked-up").is_some());
+        assert_eq!(temp.total(), 6000);
+    }
+
+    #[test]
+    fn oversized_images_are_refused() {
+        let temp = TempCache::new("oversized", 8000);
+        assert_eq!(temp.cache.max_image_bytes(), 1000);
+        assert!(temp.cache.store("http://server/big", "jpg", &[0; 1001]).is_err());
+        assert_eq!(temp.total(), 0);
+        assert_eq!(ArtCache::new(PathBuf::new(), u64::MAX).max_image_bytes(), MAX_IMAGE_BYTES);
+    }
+
+    #[test]
+    fn clear_empties_the_cache() {
+        let temp = TempCache::new("clear", 8000);
+        temp.store_aged("http://server/a", 500, 0);
+        temp.store_aged("http://server/b", 700, 0);
+        assert_eq!(temp.cache.clear().unwrap(), 1200);
+        assert!(temp.cache.files().unwrap().is_empty());
+        assert_eq!(temp.cache.lookup("http://server/a"), None);
+        // Clearing a cache that was never written is not an error
+        assert_eq!(TempCache::new("never-written", 8000).cache.clear().unwrap(), 0);
+    }
+}
@@ -0,0 +1,223 @@
+// ========================================================================
+// Auto Pause
+// ========================================================================
+//
+// Decides when to pause and resume playback in response to triggers such as
+// the window being minimized or the screen locking. Each trigger has one
+// setting per media type in the `autopause` section, named
+// `<trigger>_<media>`:
+//
+//   "autopause": { "minimize_video": true, "lock_audio": false, ... }
+//
+// Playback is only resumed if the engine paused it and every trigger that
+// applies to the current media has cleared. Resuming by hand while paused
+// hands control back to the user: nothing is resumed later.
+
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Minimized,
+    FocusLost,
+    ScreenLocked,
+}
+
+impl Trigger {
+    fn as_str(self) -> &'static str {
+        match self {
+            Trigger::Minimized => "minimize",
+            Trigger::FocusLost => "focus",
+            Trigger::ScreenLocked => "lock",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Video,
+    Audio,
+}
+
+impl MediaKind {
+    // jellyfin item MediaType; anything that isn't audio is treated as video
+    pub fn from_media_type(media_type: &str) -> MediaKind {
+        if media_type.eq_ignore_ascii_case("audio") {
+            MediaKind::Audio
+        } else {
+            MediaKind::Video
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            MediaKind::Video => "video",
+            MediaKind::Audio => "audio",
+        }
+    }
+}
+
+// Setting key in the `autopause` section for a trigger and media type
+pub fn setting_key(trigger: Trigger, media: MediaKind) -> String {
+    format!("{}_{}", trigger.as_str(), media.as_str())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Pause,
+    Resume,
+}
+
+#[derive(Debug, Default)]
+pub struct AutoPause {
+    playing: bool,
+    media: Option<MediaKind>,
+    // Triggers currently in effect, whether or not they are enabled
+    active: HashSet<Trigger>,
+    paused_by_us: bool,
+}
+
+impl AutoPause {
+    // Media type of the current item, None once playback stops
+    pub fn set_media(&mut self, media: Option<MediaKind>) {
+        self.media = media;
+        if media.is_none() {
+            self.playing = false;
+            self.paused_by_us = false;
+        }
+    }
+
+    pub fn set_playing(&mut self, playing: bool) {
+        // Resumed by the user (or another client) while we held the pause
+        if playing && self.paused_by_us {
+            self.paused_by_us = false;
+        }
+        self.playing = playing;
+    }
+
+    // A trigger came into or out of effect. `enabled` reports the setting for
+    // a trigger and media type.
+    pub fn update(
+        &mut self,
+        trigger: Trigger,
+        active: bool,
+        enabled: impl Fn(Trigger, MediaKind) -> bool,
+    ) -> Option<Action> {
+        let changed = if active {
+            self.active.insert(trigger)
+        } else {
+            self.active.remove(&trigger)
+        };
+        if !changed {
+            return None;
+        }
+        let media = self.media?;
+
+        if active {
+            if self.playing && !self.paused_by_us && enabled(trigger, media) {
+                self.paused_by_us = true;
+                return Some(Action::Pause);
+            }
+            return None;
+        }
+
+        // Hold the pause while any other enabled trigger is still in effect
+        if self.paused_by_us && !self.active.iter().any(|t| enabled(*t, media)) {
+            self.paused_by_us = false;
+            return Some(Action::Resume);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all(_: Trigger, _: MediaKind) -> bool {
+        true
+    }
+
+    fn playing_video() -> AutoPause {
+        let mut engine = AutoPause::default();
+        engine.set_media(Some(MediaKind::Video));
+        engine.set_playing(true);
+        engine
+    }
+
+    #[test]
+    fn pauses_and_resumes_when_the_trigger_clears() {
+        let mut engine = playing_video();
+        assert_eq!(engine.update(Trigger::Minimized, true, all), Some(Action::Pause));
+        engine.set_playing(false);
+        // Repeated reports of the same state change nothing
+        assert_eq!(engine.update(Trigger::Minimized, true, all), None);
+        assert_eq!(engine.update(Trigger::Minimized, false, all), Some(Action::Resume));
+    }
+
+    #[test]
+    fn resumes_only_if_we_paused() {
+        // Already paused by the user
+        let mut engine = playing_video();
+        engine.set_playing(false);
+        assert_eq!(engine.update(Trigger::Minimized, true, all), None);
+        assert_eq!(engine.update(Trigger::Minimized, false, all), None);
+
+        // Trigger disabled for this media type
+        let mut engine = playing_video();
+        let lock_only = |trigger: Trigger, _: MediaKind| trigger == Trigger::ScreenLocked;
+        assert_eq!(engine.update(Trigger::Minimized, true, lock_only), None);
+        assert_eq!(engine.update(Trigger::Minimized, false, lock_only), None);
+
+        // Nothing playing
+        let mut engine = AutoPause::default();
+        assert_eq!(engine.update(Trigger::Minimized, true, all), None);
+        assert_eq!(engine.update(Trigger::Minimized, false, all), None);
+    }
+
+    #[test]
+    fn manual_resume_cancels() {
+        let mut engine = playing_video();
+        assert_eq!(engine.update(Trigger::FocusLost, true, all), Some(Action::Pause));
+        engine.set_playing(false);
+        // The user resumes while the window is still unfocused
+        engine.set_playing(true);
+        assert_eq!(engine.update(Trigger::FocusLost, false, all), None);
+    }
+
+    #[test]
+    fn holds_while_another_enabled_trigger_is_active() {
+        let mut engine = playing_video();
+        assert_eq!(engine.update(Trigger::Minimized, true, all), Some(Action::Pause));
+        engine.set_playing(false);
+        assert_eq!(engine.update(Trigger::ScreenLocked, true, all), None);
+        assert_eq!(engine.update(Trigger::Minimized, false, all), None);
+        assert_eq!(engine.update(Trigger::ScreenLocked, false, all), Some(Action::Resume));
+    }
+
+    #[test]
+    fn disabled_triggers_do_not_hold_the_pause() {
+        let mut engine = playing_video();
+        let video_minimize = |trigger: Trigger, media: MediaKind| {
+            trigger == Trigger::Minimized && media == MediaKind::Video
+        };
+        assert_eq!(engine.update(Trigger::Minimized, true, video_minimize), Some(Action::Pause));
+        engine.set_playing(false);
+        assert_eq!(engine.update(Trigger::FocusLost, true, video_minimize), None);
+        assert_eq!(engine.update(Trigger::Minimized, false, video_minimize), Some(Action::Resume));
+    }
+
+    #[test]
+    fn stopping_playback_forgets_the_pause() {
+        let mut engine = playing_video();
+        assert_eq!(engine.update(Trigger::Minimized, true, all), Some(Action::Pause));
+        engine.set_media(None);
+        assert_eq!(engine.update(Trigger::Minimized, false, all), None);
+    }
+
+    #[test]
+    fn setting_keys() {
+        assert_eq!(setting_key(Trigger::Minimized, MediaKind::Video), "minimize_video");
+        assert_eq!(setting_key(Trigger::ScreenLocked, MediaKind::from_media_type("Audio")), "lock_audio");
+        assert_eq!(setting_key(Trigger::FocusLost, MediaKind::from_media_type("Book")), "focus_video");
+    }
+}
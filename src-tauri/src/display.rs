@@ -0,0 +1,502 @@
+// ========================================================================
+// Display Mode Switching
+// ========================================================================
+//
+// Switches the output showing the video to a refresh rate that matches the
+// content frame rate (23.976 fps on a 23.976 Hz or 47.952 Hz mode, 25 fps
+// on 50 Hz, ...) and puts the original mode back afterwards. Platform access
+// goes through `DisplayBackend`; the selection logic only sees the mode
+// lists a backend reports.
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: f64,
+}
+
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}@{:.3}Hz", self.width, self.height, self.refresh_rate)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Output {
+    pub name: String,
+    pub primary: bool,
+    pub current: Option<DisplayMode>,
+    pub modes: Vec<DisplayMode>,
+}
+
+#[derive(Debug)]
+pub enum DisplayError {
+    UnknownOutput(String),
+    UnsupportedMode { output: String, mode: DisplayMode },
+    Backend(String),
+}
+
+impl fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayError::UnknownOutput(name) => write!(f, "Unknown display output '{}'", name),
+            DisplayError::UnsupportedMode { output, mode } => {
+                write!(f, "Output '{}' does not support {}", output, mode)
+            }
+            DisplayError::Backend(message) => write!(f, "Display backend error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for DisplayError {}
+
+pub trait DisplayBackend: Send {
+    fn name(&self) -> &'static str;
+    fn outputs(&mut self) -> Result<Vec<Output>, DisplayError>;
+    fn set_mode(&mut self, output: &str, mode: &DisplayMode) -> Result<(), DisplayError>;
+}
+
+// The platform backend, if any. JELLYFIN_DISPLAY_BACKEND=fake selects the
+// in-memory backend for development on machines without a supported one.
+pub fn detect_backend() -> Option<Box<dyn DisplayBackend>> {
+    if std::env::var("JELLYFIN_DISPLAY_BACKEND").as_deref() == Ok("fake") {
+        info!("Display: using the fake backend");
+        return Some(Box::new(FakeBackend::with_default_outputs()));
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(backend) = XrandrBackend::detect() {
+        info!("Display: using the X11 RandR backend");
+        return Some(Box::new(backend));
+    }
+    info!("Display: mode switching is not supported on this system");
+    None
+}
+
+// ========================================================================
+// Mode Selection
+// ========================================================================
+
+// Allowed difference between a refresh rate and a multiple of the frame
+// rate, per multiple. Tight enough to tell 23.976 from 24.
+const RATE_TOLERANCE: f64 = 0.01;
+
+// Servers and containers report NTSC rates rounded (23.976, 29.97, 59.94);
+// snap them to the exact n*1000/1001 value before matching.
+fn normalize_fps(fps: f64) -> f64 {
+    for nominal in [24.0, 30.0, 48.0, 60.0, 120.0] {
+        let ntsc = nominal * 1000.0 / 1001.0;
+        if (fps - ntsc).abs() < 0.01 {
+            return ntsc;
+        }
+    }
+    fps
+}
+
+// Best mode at the resolution of `current` for `fps`: an exact refresh rate
+// match first, then the smallest integer multiple (48 Hz, 72 Hz, 120 Hz for
+// 24 fps). None when no mode shows every frame for the same duration.
+pub fn best_mode_for_fps(current: &DisplayMode, modes: &[DisplayMode], fps: f64) -> Option<DisplayMode> {
+    if !fps.is_finite() || fps < 1.0 {
+        return None;
+    }
+    let fps = normalize_fps(fps);
+    modes
+        .iter()
+        .filter(|m| m.width == current.width && m.height == current.height)
+        .filter_map(|m| {
+            let multiple = (m.refresh_rate / fps).round();
+            if multiple < 1.0 {
+                return None;
+            }
+            let error = (m.refresh_rate - fps * multiple).abs();
+            (error <= RATE_TOLERANCE * multiple).then_some((multiple, error, m))
+        })
+        .min_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap_or(Ordering::Equal)
+                .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        })
+        .map(|(_, _, m)| m.clone())
+}
+
+// ========================================================================
+// Switcher
+// ========================================================================
+
+pub struct DisplayModeSwitcher {
+    backend: Box<dyn DisplayBackend>,
+    // Output and mode from before the first switch, restored by restore()
+    original: Option<(String, DisplayMode)>,
+}
+
+impl DisplayModeSwitcher {
+    pub fn new(backend: Box<dyn DisplayBackend>) -> Self {
+        DisplayModeSwitcher { backend, original: None }
+    }
+
+    pub fn outputs(&mut self) -> Result<Vec<Output>, DisplayError> {
+        self.backend.outputs()
+    }
+
+    // Switch `output` (the primary output when None or not found) to the best
+    // mode for `fps`. Returns true when the mode was changed.
+    pub fn match_fps(&mut self, output: Option<&str>, fps: f64) -> Result<bool, DisplayError> {
+        let outputs = self.backend.outputs()?;
+        let target = output
+            .and_then(|name| outputs.iter().find(|o| o.name == name))
+            .or_else(|| outputs.iter().find(|o| o.primary))
+            .or_else(|| outputs.first())
+            .ok_or_else(|| DisplayError::UnknownOutput(output.unwrap_or_default().to_string()))?;
+        let current = target
+            .current
+            .clone()
+            .ok_or_else(|| DisplayError::Backend(format!("output '{}' has no active mode", target.name)))?;
+
+        // A different output was switched earlier: put it back first
+        if self.original.as_ref().is_some_and(|(name, _)| name != &target.name) {
+            self.restore()?;
+        }
+        // Select from the original mode so consecutive videos don't drift
+        // away from the user's resolution
+        let base = match &self.original {
+            Some((_, mode)) => mode.clone(),
+            None => current.clone(),
+        };
+
+        let Some(mode) = best_mode_for_fps(&base, &target.modes, fps) else {
+            info!("Display: no mode on {} matches {:.3} fps", target.name, fps);
+            return Ok(false);
+        };
+        if mode == current {
+            debug!("Display: {} already at {} for {:.3} fps", target.name, mode, fps);
+            return Ok(false);
+        }
+
+        info!(
+            "Display: switching {} from {} to {} for {:.3} fps ({} backend)",
+            target.name,
+            current,
+            mode,
+            fps,
+            self.backend.name()
+        );
+        self.backend.set_mode(&target.name, &mode)?;
+        if self.original.is_none() {
+            self.original = Some((target.name.clone(), current));
+        }
+        Ok(true)
+    }
+
+    // Put back the mode from before the first switch. Returns true when a
+    // mode was restored.
+    pub fn restore(&mut self) -> Result<bool, DisplayError> {
+        let Some((output, mode)) = self.original.take() else {
+            return Ok(false);
+        };
+        info!("Display: restoring {} to {}", output, mode);
+        if let Err(e) = self.backend.set_mode(&output, &mode) {
+            warn!("Display: failed to restore {}: {}", output, e);
+            return Err(e);
+        }
+        Ok(true)
+    }
+}
+
+// ========================================================================
+// Fake Backend
+// ========================================================================
+
+// In-memory outputs. Lets the selection and restore logic run without
+// display hardware (JELLYFIN_DISPLAY_BACKEND=fake).
+pub struct FakeBackend {
+    outputs: Vec<Output>,
+}
+
+impl FakeBackend {
+    pub fn new(outputs: Vec<Output>) -> Self {
+        FakeBackend { outputs }
+    }
+
+    // One 1080p output offering the common TV refresh rates, at 60 Hz
+    pub fn with_default_outputs() -> Self {
+        let modes: Vec<DisplayMode> = [60.0, 59.94, 50.0, 30.0, 29.97, 25.0, 24.0, 23.976]
+            .into_iter()
+            .map(|refresh_rate| DisplayMode { width: 1920, height: 1080, refresh_rate })
+            .collect();
+        FakeBackend::new(vec![Output {
+            name: "FAKE-1".to_string(),
+            primary: true,
+            current: modes.first().cloned(),
+            modes,
+        }])
+    }
+}
+
+impl DisplayBackend for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn outputs(&mut self) -> Result<Vec<Output>, DisplayError> {
+        Ok(self.outputs.clone())
+    }
+
+    fn set_mode(&mut self, output: &str, mode: &DisplayMode) -> Result<(), DisplayError> {
+        let target = self
+            .outputs
+            .iter_mut()
+            .find(|o| o.name == output)
+            .ok_or_else(|| DisplayError::UnknownOutput(output.to_string()))?;
+        if !target.modes.contains(mode) {
+            return Err(DisplayError::UnsupportedMode {
+                output: output.to_string(),
+                mode: mode.clone(),
+            });
+        }
+        target.current = Some(mode.clone());
+        Ok(())
+    }
+}
+
+// ========================================================================
+// X11 RandR Backend (Linux)
+// ========================================================================
+//
+// Drives RandR through the `xrandr` tool rather than linking libXrandr.
+// Not used under Wayland, where XWayland's RandR outputs cannot change mode.
+
+#[cfg(target_os = "linux")]
+pub struct XrandrBackend;
+
+#[cfg(target_os = "linux")]
+impl XrandrBackend {
+    pub fn detect() -> Option<Self> {
+        if std::env::var_os("DISPLAY").is_none() || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            return None;
+        }
+        run_xrandr(&["--version"]).ok()?;
+        Some(XrandrBackend)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn run_xrandr(args: &[&str]) -> Result<String, DisplayError> {
+    let output = std::process::Command::new("xrandr")
+        .args(args)
+        .output()
+        .map_err(|e| DisplayError::Backend(format!("failed to run xrandr: {}", e)))?;
+    if !output.status.success() {
+        return Err(DisplayError::Backend(format!(
+            "xrandr {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Parse `xrandr --query`:
+//   HDMI-1 connected primary 1920x1080+0+0 (normal left ...) 531mm x 299mm
+//      1920x1080     60.00*+  50.00    59.94    24.00    23.98
+//      1280x720      60.00    50.00
+// `*` marks the current mode and `+` the preferred one. Interlaced modes
+// and disconnected outputs are skipped.
+#[cfg(target_os = "linux")]
+pub fn parse_xrandr(text: &str) -> Vec<Output> {
+    let mut outputs: Vec<Output> = Vec::new();
+    let mut in_connected_output = false;
+
+    for line in text.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            in_connected_output = words.next() == Some("connected");
+            if in_connected_output {
+                outputs.push(Output {
+                    name: name.to_string(),
+                    primary: line.contains(" primary "),
+                    current: None,
+                    modes: Vec::new(),
+                });
+            }
+            continue;
+        }
+        let Some(output) = outputs.last_mut().filter(|_| in_connected_output) else {
+            continue;
+        };
+
+        let mut words = line.split_whitespace();
+        let Some((width, height)) = words.next().and_then(|res| {
+            let (w, h) = res.split_once('x')?;
+            Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?))
+        }) else {
+            // Interlaced ("1920x1080i") or a --verbose property line
+            continue;
+        };
+        for word in words {
+            let rate = word.trim_end_matches(['*', '+']);
+            let Ok(refresh_rate) = rate.parse::<f64>() else {
+                continue;
+            };
+            let mode = DisplayMode { width, height, refresh_rate };
+            if word.contains('*') {
+                output.current = Some(mode.clone());
+            }
+            output.modes.push(mode);
+        }
+    }
+    outputs
+}
+
+#[cfg(target_os = "linux")]
+impl DisplayBackend for XrandrBackend {
+    fn name(&self) -> &'static str {
+        "xrandr"
+    }
+
+    fn outputs(&mut self) -> Result<Vec<Output>, DisplayError> {
+        Ok(parse_xrandr(&run_xrandr(&["--query"])?))
+    }
+
+    fn set_mode(&mut self, output: &str, mode: &DisplayMode) -> Result<(), DisplayError> {
+        let resolution = format!("{}x{}", mode.width, mode.height);
+        let rate = format!("{:.3}", mode.refresh_rate);
+        run_xrandr(&["--output", output, "--mode", &resolution, "--rate", &rate]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modes(width: u32, height: u32, rates: &[f64]) -> Vec<DisplayMode> {
+        rates
+            .iter()
+            .map(|&refresh_rate| DisplayMode { width, height, refresh_rate })
+            .collect()
+    }
+
+    fn rate(mode: Option<DisplayMode>) -> Option<f64> {
+        mode.map(|m| m.refresh_rate)
+    }
+
+    #[test]
+    fn ntsc_and_integer_rates_pick_their_own_mode() {
+        let modes = modes(1920, 1080, &[60.0, 59.94, 50.0, 24.0, 23.976]);
+        let current = &modes[0];
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 23.976)), Some(23.976));
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 24.0)), Some(24.0));
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 59.94)), Some(59.94));
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 60.0)), Some(60.0));
+    }
+
+    #[test]
+    fn smallest_multiple_is_preferred() {
+        let modes = modes(1920, 1080, &[120.0, 100.0, 60.0, 50.0]);
+        let current = &modes[2];
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 25.0)), Some(50.0));
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 24.0)), Some(120.0));
+        assert_eq!(rate(best_mode_for_fps(current, &modes, 30.0)), Some(60.0));
+
+        let ntsc = self::modes(1920, 1080, &[60.0, 47.952]);
+        assert_eq!(rate(best_mode_for_fps(&ntsc[0], &ntsc, 23.976)), Some(47.952));
+    }
+
+    #[test]
+    fn no_suitable_mode() {
+        let modes = modes(1920, 1080, &[60.0, 59.94]);
+        let current = &modes[0];
+        // 24 fps on 60 Hz needs 3:2 pulldown, which is no match
+        assert_eq!(best_mode_for_fps(current, &modes, 24.0), None);
+        assert_eq!(best_mode_for_fps(current, &modes, 25.0), None);
+        assert_eq!(best_mode_for_fps(current, &modes, 0.0), None);
+        assert_eq!(best_mode_for_fps(current, &modes, f64::NAN), None);
+
+        // Only modes at the current resolution count
+        let mut other = self::modes(1280, 720, &[24.0]);
+        other.push(current.clone());
+        assert_eq!(best_mode_for_fps(current, &other, 24.0), None);
+    }
+
+    #[test]
+    fn rounded_ntsc_rates_are_normalized() {
+        assert_eq!(normalize_fps(23.976), 24000.0 / 1001.0);
+        assert_eq!(normalize_fps(23.98), 24000.0 / 1001.0);
+        assert_eq!(normalize_fps(29.97), 30000.0 / 1001.0);
+        assert_eq!(normalize_fps(59.94), 60000.0 / 1001.0);
+        assert_eq!(normalize_fps(24.0), 24.0);
+        assert_eq!(normalize_fps(25.0), 25.0);
+        assert_eq!(normalize_fps(50.0), 50.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn xrandr_query_is_parsed() {
+        let text = "\
+Screen 0: minimum 320 x 200, current 3840 x 1080, maximum 16384 x 16384
+HDMI-1 connected primary 1920x1080+0+0 (normal left inverted right x axis y axis) 531mm x 299mm
+   1920x1080     60.00*+  50.00    59.94    24.00    23.98
+   1920x1080i    60.00    50.00    59.94
+   1280x720      60.00    50.00    59.94
+DP-1 disconnected (normal left inverted right x axis y axis)
+   1024x768      60.00
+DP-2 connected 1920x1080+1920+0 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00 +  74.97*   50.00
+";
+        let outputs = parse_xrandr(text);
+        assert_eq!(outputs.len(), 2);
+
+        let hdmi = &outputs[0];
+        assert_eq!(hdmi.name, "HDMI-1");
+        assert!(hdmi.primary);
+        assert_eq!(hdmi.current, Some(DisplayMode { width: 1920, height: 1080, refresh_rate: 60.0 }));
+        let mut expected = modes(1920, 1080, &[60.0, 50.0, 59.94, 24.0, 23.98]);
+        expected.extend(modes(1280, 720, &[60.0, 50.0, 59.94]));
+        assert_eq!(hdmi.modes, expected);
+
+        let dp = &outputs[1];
+        assert_eq!(dp.name, "DP-2");
+        assert!(!dp.primary);
+        assert_eq!(dp.current, Some(DisplayMode { width: 1920, height: 1080, refresh_rate: 74.97 }));
+        assert_eq!(dp.modes, modes(1920, 1080, &[60.0, 74.97, 50.0]));
+    }
+
+    fn current_rate(switcher: &mut DisplayModeSwitcher) -> f64 {
+        let outputs = switcher.outputs().unwrap();
+        outputs[0].current.as_ref().unwrap().refresh_rate
+    }
+
+    #[test]
+    fn switch_then_restore() {
+        let mut switcher = DisplayModeSwitcher::new(Box::new(FakeBackend::with_default_outputs()));
+        assert!(!switcher.restore().unwrap());
+
+        assert!(switcher.match_fps(None, 23.976).unwrap());
+        assert_eq!(current_rate(&mut switcher), 23.976);
+        // Already there
+        assert!(!switcher.match_fps(Some("FAKE-1"), 23.976).unwrap());
+
+        assert!(switcher.match_fps(Some("FAKE-1"), 25.0).unwrap());
+        assert_eq!(current_rate(&mut switcher), 25.0);
+
+        // Back to the mode from before the first switch, once
+        assert!(switcher.restore().unwrap());
+        assert_eq!(current_rate(&mut switcher), 60.0);
+        assert!(!switcher.restore().unwrap());
+    }
+
+    #[test]
+    fn unmatched_rate_leaves_the_mode_alone() {
+        let mut switcher = DisplayModeSwitcher::new(Box::new(FakeBackend::with_default_outputs()));
+        assert!(!switcher.match_fps(None, 17.0).unwrap());
+        assert_eq!(current_rate(&mut switcher), 60.0);
+        assert!(!switcher.restore().unwrap());
+    }
+}
@@ -0,0 +1,252 @@
+// ========================================================================
+// Keymap
+// ========================================================================
+//
+// In-app keyboard bindings, stored in the `input` settings section as one
+// list of key combinations per action:
+//
+//   "input": { "play_pause": ["Space", "P", "MediaPlayPause"], "close": ["Ctrl+Q"] }
+//
+// A combination is any of Ctrl, Alt, Shift and Meta followed by a
+// KeyboardEvent.key value. Single characters match either case, " " and "+"
+// are written Space and Plus (a trailing "+" also works, as in "Ctrl++"),
+// modifiers may come in any order, and Shift only has to be held when the
+// combination names it, so "P" fires for Shift+P unless "Shift+P" is bound.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+    pub key: String,
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // A lone space is the key itself, so check before trimming
+        if text == " " {
+            return Ok(KeyCombo::plain(canonical_key(text)));
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("empty key combination".to_string());
+        }
+        if text == "+" {
+            return Ok(KeyCombo::plain(canonical_key(text)));
+        }
+
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        // A trailing "+" is the key itself: "Ctrl++" splits into "Ctrl", "", ""
+        let key = if parts.len() > 2 && parts.ends_with(&["", ""]) {
+            parts.truncate(parts.len() - 2);
+            "+"
+        } else {
+            parts.pop().unwrap_or_default()
+        };
+        if key.is_empty() || is_modifier(key) {
+            return Err(format!("'{}' has no key after the modifiers", text));
+        }
+
+        let mut combo = KeyCombo::plain(canonical_key(key));
+        for part in parts {
+            let flag = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut combo.ctrl,
+                "alt" | "option" => &mut combo.alt,
+                "shift" => &mut combo.shift,
+                "meta" | "super" | "cmd" | "command" => &mut combo.meta,
+                _ => return Err(format!("'{}' in '{}' is not a modifier", part, text)),
+            };
+            if *flag {
+                return Err(format!("'{}' repeats a modifier", text));
+            }
+            *flag = true;
+        }
+        Ok(combo)
+    }
+}
+
+// Canonical form: modifiers in Ctrl, Alt, Shift, Meta order
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [(self.ctrl, "Ctrl"), (self.alt, "Alt"), (self.shift, "Shift"), (self.meta, "Meta")] {
+            if held {
+                write!(f, "{}+", name)?;
+            }
+        }
+        f.write_str(&self.key)
+    }
+}
+
+impl KeyCombo {
+    fn plain(key: String) -> Self {
+        KeyCombo {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+            key,
+        }
+    }
+
+    // Key names are compared case-insensitively
+    fn identity(&self) -> String {
+        self.to_string().to_lowercase()
+    }
+}
+
+fn is_modifier(part: &str) -> bool {
+    matches!(
+        part.to_ascii_lowercase().as_str(),
+        "ctrl" | "control" | "alt" | "option" | "shift" | "meta" | "super" | "cmd" | "command"
+    )
+}
+
+fn canonical_key(key: &str) -> String {
+    match key {
+        " " => "Space".to_string(),
+        "+" => "Plus".to_string(),
+        _ if key.eq_ignore_ascii_case("space") => "Space".to_string(),
+        _ if key.eq_ignore_ascii_case("plus") => "Plus".to_string(),
+        _ if key.chars().count() == 1 => key.to_uppercase(),
+        _ => key.to_string(),
+    }
+}
+
+// Parse a binding list, rejecting malformed and repeated combinations.
+// Returns the combinations in canonical form.
+pub fn parse_keys<'a>(keys: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, String> {
+    let mut parsed: Vec<KeyCombo> = Vec::new();
+    for text in keys {
+        let combo: KeyCombo = text.parse()?;
+        if parsed.iter().any(|c| c.identity() == combo.identity()) {
+            return Err(format!("'{}' is listed twice", combo));
+        }
+        parsed.push(combo);
+    }
+    Ok(parsed.iter().map(KeyCombo::to_string).collect())
+}
+
+// A combination bound to more than one action
+#[derive(Debug, Clone, Serialize)]
+pub struct Conflict {
+    pub keys: String,
+    pub actions: Vec<String>,
+}
+
+// Conflicts across `bindings` (action, combinations). Unparseable entries
+// are skipped; they cannot be stored in the first place.
+pub fn find_conflicts<'a>(bindings: impl IntoIterator<Item = (&'a str, &'a [String])>) -> Vec<Conflict> {
+    let mut owners: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+    for (action, keys) in bindings {
+        for combo in keys.iter().filter_map(|k| k.parse::<KeyCombo>().ok()) {
+            let (_, actions) = owners
+                .entry(combo.identity())
+                .or_insert_with(|| (combo.to_string(), Vec::new()));
+            if !actions.iter().any(|a| a == action) {
+                actions.push(action.to_string());
+            }
+        }
+    }
+    owners
+        .into_values()
+        .filter(|(_, actions)| actions.len() > 1)
+        .map(|(keys, actions)| Conflict { keys, actions })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(text: &str) -> Result<String, String> {
+        text.parse::<KeyCombo>().map(|combo| combo.to_string())
+    }
+
+    #[test]
+    fn combinations_parse_to_canonical_form() {
+        let cases = [
+            ("Space", "Space"),
+            (" ", "Space"),
+            ("space", "Space"),
+            ("+", "Plus"),
+            ("plus", "Plus"),
+            ("p", "P"),
+            ("  Ctrl + Q  ", "Ctrl+Q"),
+            ("MediaPlayPause", "MediaPlayPause"),
+            ("ArrowLeft", "ArrowLeft"),
+            // Modifiers in any order come out as Ctrl, Alt, Shift, Meta
+            ("Shift+Ctrl+p", "Ctrl+Shift+P"),
+            ("Meta+Shift+Alt+Ctrl+x", "Ctrl+Alt+Shift+Meta+X"),
+            // Aliases
+            ("control+option+a", "Ctrl+Alt+A"),
+            ("cmd+q", "Meta+Q"),
+            ("Command+q", "Meta+Q"),
+            ("super+Tab", "Meta+Tab"),
+            ("SHIFT+CTRL+Space", "Ctrl+Shift+Space"),
+            // The + key
+            ("Ctrl++", "Ctrl+Plus"),
+            ("Ctrl + +", "Ctrl+Plus"),
+            ("Ctrl+Plus", "Ctrl+Plus"),
+            ("Alt+Shift++", "Alt+Shift+Plus"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(canonical(text).as_deref(), Ok(expected), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn invalid_combinations_are_rejected() {
+        let cases = [
+            "", "   ", "Ctrl", "ctrl+shift", "Ctrl+", "Q+Ctrl", "Hyper+Q", "Ctrl+A+B", "Ctrl+Ctrl+Q",
+            "Ctrl+Control+Q", "cmd+Meta+Q", "++", "Ctrl+++", "+Q",
+        ];
+        for text in cases {
+            assert!(canonical(text).is_err(), "{:?} parsed as {:?}", text, canonical(text));
+        }
+    }
+
+    #[test]
+    fn binding_lists_reject_repeats() {
+        assert_eq!(parse_keys(["p", "ctrl+q", "Ctrl++"]), Ok(vec!["P".into(), "Ctrl+Q".into(), "Ctrl+Plus".into()]));
+        // Shift is part of the combination, so these are different keys
+        assert!(parse_keys(["P", "Shift+P"]).is_ok());
+        for keys in [["p", "P"], ["Ctrl+q", "control+Q"], ["Ctrl++", "Ctrl+Plus"], ["Alt+Ctrl+x", "Ctrl+Alt+X"]] {
+            assert!(parse_keys(keys).is_err(), "{:?}", keys);
+        }
+        assert!(parse_keys(["Space", "Ctrl+"]).is_err());
+    }
+
+    #[test]
+    fn conflicts_list_every_action_sharing_a_combination() {
+        let bindings: Vec<(&str, Vec<String>)> = vec![
+            ("play_pause", vec!["Space".into(), "P".into()]),
+            ("pause", vec!["p".into()]),
+            ("zoom_in", vec!["Ctrl++".into()]),
+            ("volume_up", vec!["control+plus".into(), "ArrowUp".into()]),
+            ("fullscreen", vec!["F".into(), "f".into()]),
+            ("broken", vec!["Ctrl+".into(), "Hyper+Space".into()]),
+            ("seek", vec!["Shift+P".into()]),
+        ];
+        let conflicts: Vec<(String, Vec<String>)> =
+            find_conflicts(bindings.iter().map(|(action, keys)| (*action, keys.as_slice())))
+                .into_iter()
+                .map(|c| (c.keys, c.actions))
+                .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                ("Ctrl+Plus".to_string(), vec!["zoom_in".to_string(), "volume_up".to_string()]),
+                ("P".to_string(), vec!["play_pause".to_string(), "pause".to_string()]),
+            ]
+        );
+        assert!(find_conflicts(std::iter::empty()).is_empty());
+    }
+}
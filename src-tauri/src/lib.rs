@@ -1,12 +1,23 @@
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_store::StoreExt;
 
+mod artcache;
+mod autopause;
+mod display;
+mod keymap;
+#[cfg(target_os = "linux")]
+mod mpris;
+mod policy;
+mod settings;
+
 // JS injection scripts - run at document_start on every page load
 const INJECTION_SCRIPT: &str = include_str!("../native/injection.js");
 const MPV_VIDEO_PLAYER: &str = include_str!("../native/mpvVideoPlayer.js");
@@ -30,6 +41,26 @@ pub struct ServerInfo {
 // Shared cancellation flag for server connectivity checks
 struct ConnectivityCancelFlag(Arc<AtomicBool>);
 
+// Server the webview is connected to. Its Id scopes per-server settings
+// overrides; Ids are learned from successful connectivity checks. Its URL
+// is the one origin besides the connect page the webview may navigate to.
+#[derive(Default)]
+struct ServerState {
+    known: Mutex<HashMap<String, String>>,
+    active: Mutex<Option<String>>,
+    active_url: Mutex<Option<tauri::Url>>,
+}
+
+impl ServerState {
+    fn active_id(&self) -> Option<String> {
+        self.active.lock().unwrap().clone()
+    }
+
+    fn active_url(&self) -> Option<tauri::Url> {
+        self.active_url.lock().unwrap().clone()
+    }
+}
+
 // ========================================================================
 // Server Commands
 // ========================================================================
@@ -38,6 +69,7 @@ struct ConnectivityCancelFlag(Arc<AtomicBool>);
 async fn check_server_connectivity(
     url: String,
     cancel_flag: State<'_, ConnectivityCancelFlag>,
+    servers: State<'_, ServerState>,
 ) -> Result<ServerInfo, String> {
     info!("Checking server connectivity: {}", url);
     // Reset flag at start of new check
@@ -85,6 +117,11 @@ async fn check_server_connectivity(
             format!("Invalid server response: {}", e)
         })?;
     info!("Connected to server: {} v{} (id={})", server_info.name, server_info.version, server_info.id);
+    servers
+        .known
+        .lock()
+        .unwrap()
+        .insert(url.trim_end_matches('/').to_string(), server_info.id.clone());
     Ok(server_info)
 }
 
@@ -95,7 +132,14 @@ fn cancel_server_connectivity(cancel_flag: State<'_, ConnectivityCancelFlag>) {
 }
 
 #[tauri::command]
-async fn save_server_url(app: AppHandle, url: String) -> Result<(), String> {
+async fn save_server_url(
+    app: AppHandle,
+    webview: tauri::Webview,
+    url: String,
+    policy: State<'_, policy::Policy>,
+) -> Result<(), policy::PolicyError> {
+    authorize_ipc(&app, &webview, "save_server_url")?;
+    policy.check_server_url(&url)?;
     info!("Saving server URL: {}", url);
     let store = app.store("settings.json").map_err(|e| {
         error!("Failed to open settings store: {}", e);
@@ -107,8 +151,15 @@ async fn save_server_url(app: AppHandle, url: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn get_saved_server(app: AppHandle) -> Result<Option<String>, String> {
+async fn get_saved_server(
+    app: AppHandle,
+    policy: State<'_, policy::Policy>,
+) -> Result<Option<String>, String> {
     debug!("Loading saved server URL");
+    if let Some(url) = policy.locked_server_url() {
+        info!("Using server URL locked by policy: {}", url);
+        return Ok(Some(url.to_string()));
+    }
     let store = app.store("settings.json").map_err(|e| {
         error!("Failed to open settings store: {}", e);
         e.to_string()
@@ -121,19 +172,31 @@ async fn get_saved_server(app: AppHandle) -> Result<Option<String>, String> {
         }
         None => {
             info!("No saved server URL found");
-            Ok(None)
+            Ok(policy.server_url().map(String::from))
         }
     };
     result
 }
 
 #[tauri::command]
-async fn navigate_to_server(app: AppHandle, url: String) -> Result<(), String> {
-    let webview = app
+async fn navigate_to_server(
+    app: AppHandle,
+    webview: tauri::Webview,
+    url: String,
+    servers: State<'_, ServerState>,
+    policy: State<'_, policy::Policy>,
+) -> Result<(), policy::PolicyError> {
+    // The active server's origin is trusted by authorize_ipc, so only an
+    // already trusted caller may choose it
+    authorize_ipc(&app, &webview, "navigate_to_server")?;
+    policy.check_server_url(&url)?;
+    set_active_server(&app, &servers, &url);
+
+    let window = app
         .get_webview_window("main")
         .ok_or_else(|| {
             error!("navigate_to_server: main window not found");
-            "Main window not found".to_string()
+            policy::PolicyError::from("Main window not found".to_string())
         })?;
 
     let nav_url = format!("{}/web/index.html", url.trim_end_matches('/'));
@@ -142,211 +205,2020 @@ async fn navigate_to_server(app: AppHandle, url: String) -> Result<(), String> {
         error!("Failed to parse navigation URL: {}", e);
         format!("Invalid URL: {}", e)
     })?;
-    webview
+    window
         .navigate(parsed)
         .map_err(|e| {
             error!("Failed to navigate webview: {}", e);
-            e.to_string()
+            e.to_string().into()
         })
 }
 
+// Record `url` as the active server. The Id comes from an earlier
+// connectivity check, falling back to the one persisted for the saved server.
+fn set_active_server(app: &AppHandle, servers: &ServerState, url: &str) {
+    let url = url.trim_end_matches('/').to_string();
+    *servers.active_url.lock().unwrap() = url.parse().ok();
+    let store = app.store("settings.json").ok();
+    let known_id = servers.known.lock().unwrap().get(&url).cloned();
+    let id = known_id.or_else(|| {
+        let store = store.as_ref()?;
+        let saved_url = store.get("server_url")?;
+        if saved_url.as_str()?.trim_end_matches('/') != url {
+            return None;
+        }
+        store.get("server_id")?.as_str().map(String::from)
+    });
+
+    match id {
+        Some(id) => {
+            info!("Active server: {} (id={})", url, id);
+            if let Some(store) = store {
+                store.set("server_id", serde_json::json!(id));
+            }
+            *servers.active.lock().unwrap() = Some(id);
+        }
+        None => {
+            warn!("Active server {} has no known Id; per-server settings disabled", url);
+            *servers.active.lock().unwrap() = None;
+        }
+    }
+}
+
 // ========================================================================
 // Settings Commands
 // ========================================================================
 
+// A single setting write, as seen by listeners
+struct SettingChange {
+    section: String,
+    key: String,
+    store_key: String,
+    // Raw value at `store_key` before the write, for rollback
+    stored: Option<Value>,
+    // Effective values before and after the write
+    old_value: Value,
+    value: Value,
+}
+
+// Policy locks win over settings.json; policy defaults replace schema
+// defaults for keys that were never set
+fn resolve_setting<R: tauri::Runtime>(
+    store: &tauri_plugin_store::Store<R>,
+    policy: &policy::Policy,
+    server_id: Option<&str>,
+    section: &str,
+    key: &str,
+) -> (Value, settings::SettingSource) {
+    if let Some(value) = policy.locked_value(section, key) {
+        return (value.clone(), settings::SettingSource::Policy);
+    }
+    match settings::resolve(section, key, server_id, |k| store.get(k)) {
+        Some((value, source)) if source != settings::SettingSource::Default => (value, source),
+        resolved => match policy.default_value(section, key) {
+            Some(value) => (value.clone(), settings::SettingSource::Default),
+            None => resolved.unwrap_or((Value::Null, settings::SettingSource::Default)),
+        },
+    }
+}
+
+// Effective value of one setting for the active server
+fn effective_setting(app: &AppHandle, section: &str, key: &str) -> Value {
+    let Ok(store) = app.store("settings.json") else {
+        return Value::Null;
+    };
+    let policy = app.state::<policy::Policy>();
+    let server_id = app.state::<ServerState>().active_id();
+    resolve_setting(&store, &policy, server_id.as_deref(), section, key).0
+}
+
+fn scoped_store_key(
+    scope: settings::SettingsScope,
+    server_id: Option<&str>,
+    section: &str,
+    key: &str,
+) -> Result<String, String> {
+    settings::check_scope(section, scope).map_err(|e| e.to_string())?;
+    match scope {
+        settings::SettingsScope::Global => Ok(settings::store_key(section, key)),
+        settings::SettingsScope::Server => server_id
+            .map(|id| settings::server_store_key(id, section, key))
+            .ok_or_else(|| "No active server for a server-scoped setting".to_string()),
+    }
+}
+
+// Emit `settings-value-changed` per key (kept for compatibility) and one
+// `settings-values-changed` with the full diff. Only keys whose effective
+// value actually changed are reported.
+fn emit_setting_changes(app: &AppHandle, changes: &[SettingChange]) {
+    let changes: Vec<&SettingChange> = changes.iter().filter(|c| c.old_value != c.value).collect();
+    if changes.is_empty() {
+        return;
+    }
+
+    for change in &changes {
+        app.emit(
+            "settings-value-changed",
+            serde_json::json!({
+                "section": change.section,
+                "key": change.key,
+                "value": change.value,
+            }),
+        )
+        .ok();
+    }
+
+    let diff: Vec<Value> = changes
+        .iter()
+        .map(|change| {
+            serde_json::json!({
+                "section": change.section,
+                "key": change.key,
+                "oldValue": change.old_value,
+                "value": change.value,
+            })
+        })
+        .collect();
+    debug!("settings: {} keys changed", diff.len());
+    app.emit("settings-values-changed", serde_json::json!({ "changes": diff }))
+        .ok();
+}
+
+// Writes are all-or-nothing: every value is validated before anything is
+// written, the store is saved once, and a failed save rolls the cache back.
+fn write_settings(
+    app: &AppHandle,
+    server_id: Option<&str>,
+    scope: settings::SettingsScope,
+    pending: Vec<(String, String, Value)>,
+) -> Result<(), policy::PolicyError> {
+    let policy = app.state::<policy::Policy>();
+    let mut targets: Vec<(String, String, String, Value)> = Vec::with_capacity(pending.len());
+    for (section, key, value) in pending {
+        if policy.is_locked(&section, &key) {
+            warn!("Settings write rejected: {}.{} is locked by policy", section, key);
+            return Err(policy::PolicyError::Locked(format!("{}.{}", section, key)));
+        }
+        settings::validate(&section, &key, &value).map_err(|e| {
+            warn!("Settings write rejected: {}", e);
+            e.to_string()
+        })?;
+        let store_key = scoped_store_key(scope, server_id, &section, &key).map_err(|e| {
+            warn!("Settings write rejected: {}", e);
+            e
+        })?;
+        targets.push((section, key, store_key, value));
+    }
+
+    let changes = write_settings_file(app, |store| {
+        let mut changes: Vec<SettingChange> = Vec::new();
+        for (section, key, store_key, value) in targets {
+            let stored = store.get(&store_key);
+            if stored.as_ref() == Some(&value) {
+                continue;
+            }
+            let (old_value, _) = resolve_setting(store, &policy, server_id, &section, &key);
+            store.set(&store_key, value);
+            let (new_value, _) = resolve_setting(store, &policy, server_id, &section, &key);
+            changes.push(SettingChange {
+                section,
+                key,
+                store_key,
+                stored,
+                old_value,
+                value: new_value,
+            });
+        }
+        if changes.is_empty() {
+            debug!("Settings write: nothing changed");
+            return Ok(changes);
+        }
+        save_or_roll_back(store, &changes)?;
+        Ok::<_, String>(changes)
+    })?;
+
+    emit_setting_changes(app, &changes);
+    Ok(())
+}
+
+// Save the store; if that fails, put back what `changes` overwrote
+fn save_or_roll_back<R: tauri::Runtime>(
+    store: &tauri_plugin_store::Store<R>,
+    changes: &[SettingChange],
+) -> Result<(), String> {
+    let Err(e) = store.save() else {
+        return Ok(());
+    };
+    error!("Settings save failed, rolling back {} keys: {}", changes.len(), e);
+    for change in changes {
+        match &change.stored {
+            Some(prev) => store.set(&change.store_key, prev.clone()),
+            None => {
+                store.delete(&change.store_key);
+            }
+        }
+    }
+    Err(format!("Failed to save settings: {}", e))
+}
+
 #[tauri::command]
-async fn settings_get_value(app: AppHandle, section: String, key: String) -> Result<Value, String> {
+async fn settings_get_value(
+    app: AppHandle,
+    servers: State<'_, ServerState>,
+    policy: State<'_, policy::Policy>,
+    section: String,
+    key: String,
+) -> Result<Value, String> {
     debug!("settings_get_value: {}.{}", section, key);
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    let store_key = format!("settings.{}.{}", section, key);
-    let val = store.get(&store_key).unwrap_or(Value::Null);
+    let server_id = servers.active_id();
+    let (val, _) = resolve_setting(&store, &policy, server_id.as_deref(), &section, &key);
     debug!("settings_get_value: {}.{} = {:?}", section, key, val);
     Ok(val)
 }
 
+// Effective value plus where it came from: "server", "global", "default" or
+// "policy" (locked by the admin policy)
 #[tauri::command]
-async fn settings_set_value(
+async fn settings_get_effective(
     app: AppHandle,
+    servers: State<'_, ServerState>,
+    policy: State<'_, policy::Policy>,
     section: String,
     key: String,
-    value: Value,
-) -> Result<(), String> {
+) -> Result<Value, String> {
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    let store_key = format!("settings.{}.{}", section, key);
-    store.set(&store_key, value.clone());
-
-    app.emit(
-        "settings-value-changed",
-        serde_json::json!({
-            "section": section,
-            "key": key,
-            "value": value,
-        }),
-    )
-    .ok();
+    let server_id = servers.active_id();
+    let (value, source) = resolve_setting(&store, &policy, server_id.as_deref(), &section, &key);
+    debug!("settings_get_effective: {}.{} = {:?} ({:?})", section, key, value, source);
+    Ok(serde_json::json!({
+        "value": value,
+        "source": source,
+        "serverId": server_id,
+    }))
+}
 
-    Ok(())
+#[tauri::command]
+async fn settings_set_value(
+    app: AppHandle,
+    webview: tauri::Webview,
+    section: String,
+    key: String,
+    value: Value,
+    scope: Option<settings::SettingsScope>,
+    pin: Option<String>,
+) -> Result<(), policy::PolicyError> {
+    let pending = vec![(section, key, value)];
+    check_protected_settings_write(&app, &webview, &pending)?;
+    check_kiosk_settings_write(&app, &pending, pin.as_deref())?;
+    let server_id = app.state::<ServerState>().active_id();
+    write_settings(&app, server_id.as_deref(), scope.unwrap_or_default(), pending)
 }
 
 #[tauri::command]
 async fn settings_set_values(
     app: AppHandle,
+    webview: tauri::Webview,
+    servers: State<'_, ServerState>,
     values: serde_json::Map<String, Value>,
-) -> Result<(), String> {
+    scope: Option<settings::SettingsScope>,
+    pin: Option<String>,
+) -> Result<(), policy::PolicyError> {
     debug!("settings_set_values: {} sections", values.len());
-    let store = app.store("settings.json").map_err(|e| e.to_string())?;
-
-    let mut changed: Vec<(String, String, Value)> = Vec::new();
+    let mut pending: Vec<(String, String, Value)> = Vec::new();
     for (section, section_vals) in &values {
         if let Some(obj) = section_vals.as_object() {
             for (key, val) in obj {
-                let store_key = format!("settings.{}.{}", section, key);
-                store.set(&store_key, val.clone());
-                changed.push((section.clone(), key.clone(), val.clone()));
+                pending.push((section.clone(), key.clone(), val.clone()));
             }
         }
     }
+    check_protected_settings_write(&app, &webview, &pending)?;
+    check_kiosk_settings_write(&app, &pending, pin.as_deref())?;
+    let server_id = servers.active_id();
+    write_settings(&app, server_id.as_deref(), scope.unwrap_or_default(), pending)
+}
 
-    // Emit change events for each value
-    for (section, key, value) in changed {
-        app.emit(
-            "settings-value-changed",
-            serde_json::json!({
-                "section": section,
-                "key": key,
-                "value": value,
-            }),
-        )
-        .ok();
+// Settings only the bundled app pages may write. main.externalUrlSchemes
+// decides which links skip the confirmation dialog, so a server page must
+// not be able to widen it; admins set it through the policy instead.
+const PROTECTED_SETTINGS: &[(&str, &str)] = &[("main", "externalUrlSchemes")];
+
+fn check_protected_settings_write(
+    app: &AppHandle,
+    webview: &tauri::Webview,
+    pending: &[(String, String, Value)],
+) -> Result<(), policy::PolicyError> {
+    let Some((section, key, _)) = pending
+        .iter()
+        .find(|(section, key, _)| PROTECTED_SETTINGS.contains(&(section.as_str(), key.as_str())))
+    else {
+        return Ok(());
+    };
+    let url = webview.url().map_err(|e| e.to_string())?;
+    if is_local_app_url(app, &url) {
+        return Ok(());
     }
+    let name = format!("{}.{}", section, key);
+    app.state::<IpcAudit>().record(url.origin().ascii_serialization(), &format!("settings write {}", name));
+    Err(policy::PolicyError::Forbidden(name))
+}
+
+// Remove the active server's override so the global value applies again
+#[tauri::command]
+async fn settings_clear_override(
+    app: AppHandle,
+    servers: State<'_, ServerState>,
+    policy: State<'_, policy::Policy>,
+    section: String,
+    key: String,
+) -> Result<(), String> {
+    let server_id = servers.active_id().ok_or("No active server")?;
+    let store_key = settings::server_store_key(&server_id, &section, &key);
+    let change = write_settings_file(&app, |store| {
+        let Some(stored) = store.get(&store_key) else {
+            return Ok(None);
+        };
+        let (old_value, _) = resolve_setting(store, &policy, Some(&server_id), &section, &key);
+        store.delete(&store_key);
+        let (value, _) = resolve_setting(store, &policy, Some(&server_id), &section, &key);
+        let change = SettingChange {
+            section: section.clone(),
+            key: key.clone(),
+            store_key: store_key.clone(),
+            stored: Some(stored),
+            old_value,
+            value,
+        };
+        save_or_roll_back(store, std::slice::from_ref(&change))?;
+        Ok::<_, String>(Some(change))
+    })?;
 
+    if let Some(change) = change {
+        info!("Cleared server override {}.{} for server {}", section, key, server_id);
+        emit_setting_changes(&app, &[change]);
+    }
     Ok(())
 }
 
+// Resets a section: removes the global values and the active server's overrides
 #[tauri::command]
-async fn settings_delete_section(app: AppHandle, section: String) -> Result<(), String> {
+async fn settings_delete_section(
+    app: AppHandle,
+    webview: tauri::Webview,
+    servers: State<'_, ServerState>,
+    section: String,
+    pin: Option<String>,
+) -> Result<(), policy::PolicyError> {
+    authorize_ipc(&app, &webview, "settings_delete_section")?;
+    if section == "main" {
+        // Deleting the section resets forceAlwaysFS
+        let reset = [("main".to_string(), "forceAlwaysFS".to_string(), Value::Bool(false))];
+        check_kiosk_settings_write(&app, &reset, pin.as_deref())?;
+    }
     debug!("settings_delete_section: {}", section);
-    let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    let prefix = format!("settings.{}.", section);
+    let mut prefixes = vec![format!("settings.{}.", section)];
+    let server_id = servers.active_id();
+    if let Some(id) = &server_id {
+        prefixes.push(format!("servers.{}.settings.{}.", id, section));
+    }
+    // A reset from a server page leaves the protected settings alone
+    let local = webview.url().is_ok_and(|url| is_local_app_url(&app, &url));
+    let policy = app.state::<policy::Policy>();
+
+    let changes = write_settings_file(&app, |store| {
+        let keys_to_delete: Vec<String> = store
+            .keys()
+            .into_iter()
+            .filter(|k| prefixes.iter().any(|p| k.starts_with(p)))
+            .filter(|k| {
+                local
+                    || !PROTECTED_SETTINGS
+                        .iter()
+                        .any(|(s, key)| *s == section && k.ends_with(&format!(".{}.{}", s, key)))
+            })
+            .collect();
+        if keys_to_delete.is_empty() {
+            return Ok(Vec::new());
+        }
 
-    let keys_to_delete: Vec<String> = store
-        .entries()
-        .into_iter()
-        .filter_map(|(k, _): (String, _)| {
-            if k.starts_with(&prefix) {
-                Some(k.clone())
-            } else {
-                None
+        // Effective values before the reset, so listeners see what changed
+        let before: Vec<(&str, Value)> = settings::section(&section)
+            .map(|schema| schema.settings)
+            .unwrap_or_default()
+            .iter()
+            .map(|desc| {
+                let (value, _) = resolve_setting(store, &policy, server_id.as_deref(), &section, desc.key);
+                (desc.key, value)
+            })
+            .collect();
+
+        let mut deleted: Vec<(String, Value)> = Vec::new();
+        for key in keys_to_delete {
+            if let Some(value) = store.get(&key) {
+                store.delete(&key);
+                deleted.push((key, value));
+            }
+        }
+        if let Err(e) = store.save() {
+            error!("Settings save failed, restoring {} keys: {}", deleted.len(), e);
+            for (key, value) in deleted {
+                store.set(key, value);
             }
+            return Err(format!("Failed to save settings: {}", e));
+        }
+
+        let changes: Vec<SettingChange> = before
+            .into_iter()
+            .map(|(key, old_value)| {
+                let store_key = settings::store_key(&section, key);
+                let (value, _) = resolve_setting(store, &policy, server_id.as_deref(), &section, key);
+                SettingChange {
+                    section: section.clone(),
+                    key: key.to_string(),
+                    stored: deleted.iter().find(|(k, _)| *k == store_key).map(|(_, v)| v.clone()),
+                    store_key,
+                    old_value,
+                    value,
+                }
+            })
+            .collect();
+        Ok(changes)
+    })?;
+
+    emit_setting_changes(&app, &changes);
+    Ok(())
+}
+
+// Merged view for the active server: schema defaults, policy defaults, global
+// values, the server's overrides, then policy-locked values. Returned as
+// { values, locked, readOnly } where `locked` lists the keys the policy pins
+// and `readOnly` the protected keys the calling page may not write.
+#[tauri::command]
+async fn settings_get_all(
+    app: AppHandle,
+    webview: tauri::Webview,
+    servers: State<'_, ServerState>,
+    policy: State<'_, policy::Policy>,
+    section: String,
+) -> Result<Value, String> {
+    debug!("settings_get_all: section={}", section);
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    let global_prefix = format!("settings.{}.", section);
+    let server_prefix = servers
+        .active_id()
+        .filter(|_| settings::is_server_scoped(&section))
+        .map(|id| format!("servers.{}.settings.{}.", id, section));
+
+    let mut result = settings::section_defaults(&section);
+    for (key, value) in result.iter_mut() {
+        if let Some(default) = policy.default_value(&section, key) {
+            *value = default.clone();
+        }
+    }
+    let mut overrides = serde_json::Map::new();
+    for (key, value) in store.entries() {
+        if let Some(setting_key) = key.strip_prefix(&global_prefix) {
+            result.insert(setting_key.to_string(), value);
+        } else if let Some(setting_key) = server_prefix.as_deref().and_then(|p| key.strip_prefix(p)) {
+            overrides.insert(setting_key.to_string(), value);
+        }
+    }
+    result.extend(overrides);
+
+    let locked = policy.locked_keys(&section);
+    for key in &locked {
+        if let Some(value) = policy.locked_value(&section, key) {
+            result.insert(key.clone(), value.clone());
+        }
+    }
+
+    let local = webview.url().is_ok_and(|url| is_local_app_url(&app, &url));
+    let read_only: Vec<&str> = PROTECTED_SETTINGS
+        .iter()
+        .filter(|(s, _)| !local && *s == section)
+        .map(|(_, key)| *key)
+        .collect();
+
+    debug!(
+        "settings_get_all: {} returned {} keys ({} locked)",
+        section,
+        result.len(),
+        locked.len()
+    );
+    Ok(serde_json::json!({ "values": result, "locked": locked, "readOnly": read_only }))
+}
+
+#[tauri::command]
+fn settings_schema() -> &'static [settings::SectionSchema] {
+    settings::SCHEMA
+}
+
+// ========================================================================
+// Keymap Commands
+// ========================================================================
+//
+// The in-app keymap lives in the `input` settings section (see keymap.rs).
+// Every change to it is followed by `input-keymap-changed` carrying the
+// full keymap, from which inputPlugin.js rebuilds its bindings.
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyBinding {
+    action: &'static str,
+    name: &'static str,
+    keys: Vec<String>,
+    defaults: Vec<String>,
+    locked: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Keymap {
+    bindings: Vec<KeyBinding>,
+    conflicts: Vec<keymap::Conflict>,
+}
+
+fn current_keymap(app: &AppHandle) -> Keymap {
+    let policy = app.state::<policy::Policy>();
+    let keys_of = |value: &Value| -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| items.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let bindings: Vec<KeyBinding> = settings::section("input")
+        .map(|s| s.settings)
+        .unwrap_or(&[])
+        .iter()
+        .map(|desc| KeyBinding {
+            action: desc.key,
+            name: desc.name,
+            keys: keys_of(&effective_setting(app, "input", desc.key)),
+            defaults: keys_of(
+                &policy
+                    .default_value("input", desc.key)
+                    .cloned()
+                    .unwrap_or_else(|| desc.default.to_value()),
+            ),
+            locked: policy.is_locked("input", desc.key),
         })
         .collect();
+    let conflicts = keymap::find_conflicts(bindings.iter().map(|b| (b.action, b.keys.as_slice())));
+    Keymap { bindings, conflicts }
+}
 
-    for key in keys_to_delete {
-        store.delete(&key);
-    }
+#[tauri::command]
+async fn input_get_keymap(app: AppHandle) -> Result<Keymap, String> {
+    Ok(current_keymap(&app))
+}
+
+// Replace the combinations of one action. Returns the conflicts in the
+// resulting keymap; conflicting bindings are stored all the same so keys
+// can be swapped one action at a time.
+#[tauri::command]
+async fn input_set_binding(
+    app: AppHandle,
+    action: String,
+    keys: Vec<String>,
+) -> Result<Vec<keymap::Conflict>, policy::PolicyError> {
+    let keys = keymap::parse_keys(keys.iter().map(String::as_str))?;
+    debug!("input_set_binding: {} = {:?}", action, keys);
+    write_settings(
+        &app,
+        None,
+        settings::SettingsScope::Global,
+        vec![("input".to_string(), action, serde_json::json!(keys))],
+    )?;
+    Ok(current_keymap(&app).conflicts)
+}
 
+// Restore the default combinations of `action`, or of every action
+#[tauri::command]
+async fn input_reset_bindings(
+    app: AppHandle,
+    policy: State<'_, policy::Policy>,
+    action: Option<String>,
+) -> Result<(), policy::PolicyError> {
+    let actions: Vec<String> = match action {
+        Some(action) => {
+            settings::descriptor("input", &action).ok_or_else(|| format!("Unknown input action '{}'", action))?;
+            vec![action]
+        }
+        None => settings::section("input")
+            .map(|s| s.settings.iter().map(|d| d.key.to_string()).collect())
+            .unwrap_or_default(),
+    };
+    info!("Resetting key bindings: {:?}", actions);
+
+    let changes = write_settings_file(&app, |store| {
+        let mut changes: Vec<SettingChange> = Vec::new();
+        for key in actions {
+            let store_key = settings::store_key("input", &key);
+            let Some(stored) = store.get(&store_key) else {
+                continue;
+            };
+            let (old_value, _) = resolve_setting(store, &policy, None, "input", &key);
+            store.delete(&store_key);
+            let (value, _) = resolve_setting(store, &policy, None, "input", &key);
+            changes.push(SettingChange {
+                section: "input".to_string(),
+                key,
+                store_key,
+                stored: Some(stored),
+                old_value,
+                value,
+            });
+        }
+        if !changes.is_empty() {
+            save_or_roll_back(store, &changes)?;
+        }
+        Ok::<_, String>(changes)
+    })?;
+    emit_setting_changes(&app, &changes);
     Ok(())
 }
 
 #[tauri::command]
-async fn settings_get_all(app: AppHandle, section: String) -> Result<Value, String> {
-    debug!("settings_get_all: section={}", section);
+async fn input_get_conflicts(app: AppHandle) -> Result<Vec<keymap::Conflict>, String> {
+    Ok(current_keymap(&app).conflicts)
+}
+
+// ========================================================================
+// Settings Migrations
+// ========================================================================
+
+// Bring settings.json up to settings::SCHEMA_VERSION. Runs once from setup,
+// before any window or command can touch the store. The previous file is
+// copied to settings.v<N>.bak.json before anything is rewritten.
+fn migrate_settings_store(app: &AppHandle) -> Result<(), String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    let from_version = store
+        .get(settings::SCHEMA_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    if from_version == settings::SCHEMA_VERSION {
+        debug!("Settings schema is current (v{})", from_version);
+        return Ok(());
+    }
+    if from_version > settings::SCHEMA_VERSION {
+        warn!(
+            "Settings schema v{} is newer than this build (v{}); leaving it untouched",
+            from_version,
+            settings::SCHEMA_VERSION
+        );
+        return Ok(());
+    }
+
+    // Fresh install — nothing to migrate, just stamp the current version
+    if store.is_empty() {
+        store.set(settings::SCHEMA_VERSION_KEY, serde_json::json!(settings::SCHEMA_VERSION));
+        return store.save().map_err(|e| e.to_string());
+    }
+
+    let path = tauri_plugin_store::resolve_store_path(app, "settings.json").map_err(|e| e.to_string())?;
+    if path.exists() {
+        let backup = path.with_file_name(format!("settings.v{}.bak.json", from_version));
+        std::fs::copy(&path, &backup).map_err(|e| {
+            error!("Failed to back up settings before migration: {}", e);
+            format!("Settings backup failed: {}", e)
+        })?;
+        info!("Backed up settings to {}", backup.display());
+    }
+
+    let before: serde_json::Map<String, Value> = store.entries().into_iter().collect();
+    let mut entries = before.clone();
+    for migration in settings::pending_migrations(from_version) {
+        info!(
+            "Applying settings migration v{}: {}",
+            migration.to_version, migration.description
+        );
+        (migration.apply)(&mut entries);
+    }
+
+    // Write back only what the migrations actually changed
+    for key in before.keys() {
+        if !entries.contains_key(key) {
+            debug!("Migration removed key: {}", key);
+            store.delete(key);
+        }
+    }
+    for (key, value) in &entries {
+        if before.get(key) != Some(value) {
+            debug!("Migration wrote key: {} = {:?}", key, value);
+            store.set(key, value.clone());
+        }
+    }
+    store.set(settings::SCHEMA_VERSION_KEY, serde_json::json!(settings::SCHEMA_VERSION));
+    store.save().map_err(|e| e.to_string())?;
+
+    info!(
+        "Settings migrated from v{} to v{}",
+        from_version,
+        settings::SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+// ========================================================================
+// Settings File Watcher
+// ========================================================================
+//
+// Admins edit settings.json by hand or push it with configuration management
+// while the app is running. Anything that changed on disk since we last saw
+// it is merged into the in-memory store. If a key was also changed locally
+// and not yet saved, the on-disk value wins and the local value is logged.
+// Invalid settings values on disk are rejected and the in-memory value kept.
+//
+// Every settings command writes through write_settings_file, which merges
+// the file first and holds the watcher off until it has saved, so a settings
+// write never overwrites an edit on disk. In between, the file is polled.
+// Other store writes, such as window geometry, go through the store plugin's
+// debounced autosave, which writes the whole in-memory map. An external edit
+// that lands less than SETTINGS_WATCH_INTERVAL before such an autosave can
+// still be overwritten.
+
+const SETTINGS_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Modification time and length
+type SettingsFileStamp = (std::time::SystemTime, u64);
+
+// settings.json as last reconciled: its stamp and contents
+type SeenSettingsFile = (Option<SettingsFileStamp>, serde_json::Map<String, Value>);
+
+struct SettingsFileState {
+    path: std::path::PathBuf,
+    seen: Mutex<SeenSettingsFile>,
+}
+
+fn settings_file_stamp(path: &std::path::Path) -> Option<SettingsFileStamp> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+fn read_settings_file(path: &std::path::Path) -> Result<serde_json::Map<String, Value>, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+}
+
+fn effective_settings_snapshot<R: tauri::Runtime>(
+    store: &tauri_plugin_store::Store<R>,
+    policy: &policy::Policy,
+    server_id: Option<&str>,
+) -> Vec<(&'static str, &'static str, Value)> {
+    settings::SCHEMA
+        .iter()
+        .flat_map(|section| section.settings.iter().map(move |d| (section.name, d.key)))
+        .map(|(section, key)| (section, key, resolve_setting(store, policy, server_id, section, key).0))
+        .collect()
+}
+
+// Merge what changed on disk into the store; returns the effective changes
+fn reconcile_settings_file(
+    app: &AppHandle,
+    previous: &serde_json::Map<String, Value>,
+    on_disk: &serde_json::Map<String, Value>,
+) -> Vec<SettingChange> {
+    let Ok(store) = app.store("settings.json") else {
+        return Vec::new();
+    };
+    let server_id = app.state::<ServerState>().active_id();
+    let policy = app.state::<policy::Policy>();
+    let before = effective_settings_snapshot(&store, &policy, server_id.as_deref());
+
+    let mut keys: Vec<&String> = previous.keys().chain(on_disk.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut applied = 0;
+    for key in keys {
+        let old_disk = previous.get(key);
+        let new_disk = on_disk.get(key);
+        if old_disk == new_disk {
+            continue;
+        }
+        let in_memory = store.get(key);
+        if in_memory.as_ref() == new_disk {
+            // Our own save, or an edit that matches what we already have
+            continue;
+        }
+
+        if let (Some(value), Some((_, section, setting_key))) = (new_disk, settings::parse_store_key(key)) {
+            if let Err(e) = settings::validate(section, setting_key, value) {
+                warn!("Ignoring external edit to {}: {}", key, e);
+                continue;
+            }
+        }
+        if in_memory.as_ref() != old_disk {
+            warn!(
+                "Conflicting edit to {}: keeping on-disk value {:?}, discarding unsaved {:?}",
+                key, new_disk, in_memory
+            );
+        }
+
+        match new_disk {
+            Some(value) => store.set(key, value.clone()),
+            None => {
+                store.delete(key);
+            }
+        }
+        applied += 1;
+    }
+
+    if applied == 0 {
+        return Vec::new();
+    }
+    info!("Applied {} external change(s) from settings.json", applied);
+
+    let after = effective_settings_snapshot(&store, &policy, server_id.as_deref());
+    before
+        .into_iter()
+        .zip(after)
+        .filter(|((_, _, old), (_, _, new))| old != new)
+        .map(|((section, key, old_value), (_, _, value))| SettingChange {
+            section: section.to_string(),
+            key: key.to_string(),
+            store_key: settings::store_key(section, key),
+            stored: None,
+            old_value,
+            value,
+        })
+        .collect()
+}
+
+// Merge external edits if settings.json changed since `seen`
+fn sync_settings_file_locked(
+    app: &AppHandle,
+    path: &std::path::Path,
+    seen: &mut SeenSettingsFile,
+) -> Vec<SettingChange> {
+    let current = settings_file_stamp(path);
+    if current.is_none() || current == seen.0 {
+        return Vec::new();
+    }
+    // A half-written file fails to parse — try again next time
+    let on_disk = match read_settings_file(path) {
+        Ok(map) => map,
+        Err(e) => {
+            debug!("settings.json not readable yet: {}", e);
+            return Vec::new();
+        }
+    };
+    let changes = reconcile_settings_file(app, &seen.1, &on_disk);
+    *seen = (current, on_disk);
+    changes
+}
+
+fn sync_settings_file(app: &AppHandle) {
+    let Some(state) = app.try_state::<SettingsFileState>() else {
+        return;
+    };
+    let changes = sync_settings_file_locked(app, &state.path, &mut state.seen.lock().unwrap());
+    // Listeners may write settings, which takes the lock again
+    emit_setting_changes(app, &changes);
+}
+
+// Run a settings write: merge external edits first, and keep the watcher
+// out until `write` has saved the store. Changes merged from disk are
+// announced once the lock is released.
+fn write_settings_file<T, E: From<String>>(
+    app: &AppHandle,
+    write: impl FnOnce(&tauri_plugin_store::Store<tauri::Wry>) -> Result<T, E>,
+) -> Result<T, E> {
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
-    let prefix = format!("settings.{}.", section);
+    let state = app.try_state::<SettingsFileState>();
+    let (result, external) = {
+        let mut seen = state.as_ref().map(|state| state.seen.lock().unwrap());
+        let external = match (&state, seen.as_deref_mut()) {
+            (Some(state), Some(seen)) => sync_settings_file_locked(app, &state.path, seen),
+            _ => Vec::new(),
+        };
+        (write(&store), external)
+    };
+    emit_setting_changes(app, &external);
+    result
+}
+
+fn spawn_settings_watcher(app: AppHandle) {
+    let path = match tauri_plugin_store::resolve_store_path(&app, "settings.json") {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("Settings watcher disabled, cannot resolve store path: {}", e);
+            return;
+        }
+    };
+    info!("Watching {} for external edits", path.display());
+    let seen = (settings_file_stamp(&path), read_settings_file(&path).unwrap_or_default());
+    app.manage(SettingsFileState {
+        path,
+        seen: Mutex::new(seen),
+    });
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SETTINGS_WATCH_INTERVAL).await;
+            sync_settings_file(&app);
+        }
+    });
+}
+
+// ========================================================================
+// Window Commands
+// ========================================================================
+
+#[tauri::command]
+async fn window_set_title(app: AppHandle, title: String) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    window.set_title(&title).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_set_fullscreen(app: AppHandle, fullscreen: bool) -> Result<(), String> {
+    if !fullscreen && kiosk_active(&app) {
+        info!("Kiosk mode: refusing to leave fullscreen");
+        return Err("Kiosk mode is active".to_string());
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    if fullscreen {
+        set_picture_in_picture(&app, &window, false, None)?;
+    }
+    set_main_fullscreen(&app, &window, fullscreen)?;
+    remember_layout_flag(&app, "fullscreen", fullscreen);
+    Ok(())
+}
+
+#[tauri::command]
+async fn window_is_fullscreen(app: AppHandle) -> Result<bool, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window not found")?;
+    window.is_fullscreen().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    set_main_always_on_top(&win, enabled)?;
+    remember_layout_flag(&app, "alwaysOnTop", enabled);
+    Ok(())
+}
+
+fn set_main_always_on_top(win: &tauri::WebviewWindow, enabled: bool) -> Result<(), String> {
+    info!("Setting always-on-top: {}", enabled);
+    win.set_always_on_top(enabled).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_is_always_on_top(app: AppHandle) -> Result<bool, String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    win.is_always_on_top().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_raise(app: AppHandle) -> Result<(), String> {
+    debug!("Raising main window");
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    win.set_focus().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_set_cursor_visible(app: AppHandle, visible: bool) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    // Layouts that hide the cursor keep it hidden
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    let hidden = read_layout_window_state(&store, app.state::<LayoutState>().current()).cursor_hidden;
+    win.set_cursor_visible(visible && !hidden).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_save_geometry(app: AppHandle) -> Result<(), String> {
+    // Each layout has its own windowed geometry
+    let prefix = app.state::<LayoutState>().current().geometry_prefix();
+    save_main_geometry(&app, prefix)
+}
+
+fn save_main_geometry(app: &AppHandle, prefix: &str) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+
+    // Don't save geometry while fullscreen — we want the windowed geometry
+    if win.is_fullscreen().unwrap_or(false) {
+        debug!("Skipping geometry save while fullscreen");
+        return Ok(());
+    }
+    // Picture-in-picture keeps its own geometry under state.pip
+    if app.state::<PipState>().active() {
+        debug!("Skipping geometry save while in picture-in-picture");
+        return Ok(());
+    }
+
+    if win.is_maximized().unwrap_or(false) {
+        // Only save the maximized flag, keep prior windowed position/size
+        store.set(format!("{}.maximized", prefix), serde_json::json!(true));
+        debug!("Saved geometry: maximized=true");
+        return Ok(());
+    }
+
+    let geometry = capture_geometry(&win)?;
+    write_geometry(&store, prefix, &geometry);
+    debug!("Saved geometry: {:?}", geometry);
+    Ok(())
+}
+
+// ========================================================================
+// Layout Modes
+// ========================================================================
+//
+// main.layout picks jellyfin-web's desktop or TV layout. Each layout keeps
+// its own window state and windowed geometry:
+//   state.layouts.<mode>.fullscreen / .alwaysOnTop / .cursorHidden / .cinema
+//   state.geometry (desktop) or state.layouts.tv.geometry (TV)
+// TV defaults to fullscreen, always on top and no cursor. Fullscreen and
+// always-on-top changes made through the window commands are remembered for
+// the active layout. --tv/--desktop and --fullscreen/--windowed override
+// the saved values for one run without changing them.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutMode {
+    Desktop,
+    Tv,
+}
+
+impl LayoutMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            LayoutMode::Desktop => "desktop",
+            LayoutMode::Tv => "tv",
+        }
+    }
+
+    fn parse(value: &str) -> Option<LayoutMode> {
+        match value {
+            "desktop" => Some(LayoutMode::Desktop),
+            "tv" => Some(LayoutMode::Tv),
+            _ => None,
+        }
+    }
+
+    fn geometry_prefix(self) -> &'static str {
+        match self {
+            LayoutMode::Desktop => "state.geometry",
+            LayoutMode::Tv => "state.layouts.tv.geometry",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LayoutWindowState {
+    fullscreen: bool,
+    always_on_top: bool,
+    cursor_hidden: bool,
+    cinema: bool,
+}
+
+fn read_layout_window_state<R: tauri::Runtime>(
+    store: &tauri_plugin_store::Store<R>,
+    mode: LayoutMode,
+) -> LayoutWindowState {
+    let get = |key: &str, default: bool| {
+        store
+            .get(format!("state.layouts.{}.{}", mode.as_str(), key))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(default)
+    };
+    match mode {
+        LayoutMode::Tv => LayoutWindowState {
+            fullscreen: get("fullscreen", true),
+            always_on_top: get("alwaysOnTop", true),
+            cursor_hidden: get("cursorHidden", true),
+            cinema: get("cinema", false),
+        },
+        LayoutMode::Desktop => LayoutWindowState {
+            fullscreen: get("fullscreen", false),
+            always_on_top: get("alwaysOnTop", false),
+            cursor_hidden: get("cursorHidden", false),
+            cinema: get("cinema", false),
+        },
+    }
+}
+
+struct LayoutState {
+    // Active layout; may differ from main.layout after --tv/--desktop
+    mode: Mutex<LayoutMode>,
+}
+
+impl LayoutState {
+    fn current(&self) -> LayoutMode {
+        *self.mode.lock().unwrap()
+    }
+}
+
+// Layout to start in: the CLI override, else main.layout
+fn startup_layout(app: &AppHandle, cli_layout: Option<LayoutMode>) -> LayoutMode {
+    cli_layout.unwrap_or_else(|| {
+        effective_setting(app, "main", "layout")
+            .as_str()
+            .and_then(LayoutMode::parse)
+            .unwrap_or(LayoutMode::Desktop)
+    })
+}
+
+// Record a window state change for the active layout
+fn remember_layout_flag(app: &AppHandle, key: &str, value: bool) {
+    if kiosk_active(app) {
+        return;
+    }
+    let Ok(store) = app.store("settings.json") else {
+        return;
+    };
+    let mode = app.state::<LayoutState>().current();
+    store.set(format!("state.layouts.{}.{}", mode.as_str(), key), serde_json::json!(value));
+}
+
+// Put the window into `mode`'s saved state. `fullscreen` overrides the
+// saved fullscreen flag (--fullscreen/--windowed).
+fn apply_layout_window_state(app: &AppHandle, win: &tauri::WebviewWindow, mode: LayoutMode, fullscreen: Option<bool>) {
+    let Ok(store) = app.store("settings.json") else {
+        return;
+    };
+    let state = read_layout_window_state(&store, mode);
+    let geometry = read_geometry(&store, mode.geometry_prefix());
+    let fullscreen = fullscreen.unwrap_or(state.fullscreen) || kiosk_active(app);
+    info!(
+        "Applying {} layout: fullscreen={}, alwaysOnTop={}, cursorHidden={}, cinema={}",
+        mode.as_str(),
+        fullscreen,
+        state.always_on_top,
+        state.cursor_hidden,
+        state.cinema
+    );
+    set_cinema_mode(app, win, state.cinema);
+
+    let windowed = &app.state::<FullscreenState>().windowed;
+    if fullscreen {
+        let _ = set_main_fullscreen(app, win, true);
+        // Leaving fullscreen goes back to this layout's windowed geometry
+        *windowed.lock().unwrap() = Some(geometry);
+    } else {
+        // The geometry from before fullscreen belongs to the previous layout
+        windowed.lock().unwrap().take();
+        let _ = set_main_fullscreen(app, win, false);
+        apply_geometry(win, &geometry);
+    }
+    let _ = set_main_always_on_top(win, state.always_on_top);
+    let _ = win.set_cursor_visible(!state.cursor_hidden);
+}
+
+// Save the current layout's geometry, apply `mode`'s window state and
+// reload the page so jellyfin-web starts in the new layout
+fn switch_layout(app: &AppHandle, mode: LayoutMode) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    if app.state::<LayoutState>().current() == mode {
+        return Ok(());
+    }
+    set_picture_in_picture(app, &win, false, None)?;
+    let layout = app.state::<LayoutState>();
+    {
+        // Held across the save so a concurrent switch sees the new mode
+        let mut current = layout.mode.lock().unwrap();
+        if *current == mode {
+            return Ok(());
+        }
+        info!("Switching layout: {} -> {}", current.as_str(), mode.as_str());
+        save_main_geometry(app, current.geometry_prefix())?;
+        *current = mode;
+    }
+    apply_layout_window_state(app, &win, mode, None);
+    win.reload().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_get_layout_mode(state: State<'_, LayoutState>) -> Result<String, String> {
+    Ok(state.current().as_str().to_string())
+}
+
+// Persist `mode` as main.layout; the settings listener switches to it.
+// After --tv/--desktop main.layout may already hold `mode` while another
+// layout is active, and an unchanged value emits no change to react to.
+#[tauri::command]
+async fn window_set_layout_mode(app: AppHandle, mode: String) -> Result<(), policy::PolicyError> {
+    let layout = LayoutMode::parse(&mode).ok_or_else(|| format!("Unknown layout mode '{}'", mode))?;
+    let saved = effective_setting(&app, "main", "layout").as_str() == Some(layout.as_str());
+    write_settings(
+        &app,
+        None,
+        settings::SettingsScope::Global,
+        vec![("main".to_string(), "layout".to_string(), serde_json::json!(mode))],
+    )?;
+    if saved {
+        switch_layout(&app, layout)?;
+    }
+    Ok(())
+}
+
+// ========================================================================
+// Cinema Mode
+// ========================================================================
+//
+// A borderless main window for the active layout. The window stays
+// resizable, so the platform keeps its resize edges. The page drags the
+// window from the areas matched by main.cinemaDragRegions through
+// window_start_drag; double-clicking them calls window_toggle_maximize.
+
+fn set_cinema_mode(app: &AppHandle, win: &tauri::WebviewWindow, enabled: bool) {
+    // Picture-in-picture restores the decorations itself when it ends
+    if !app.state::<PipState>().active() {
+        let _ = win.set_decorations(!enabled);
+        let _ = win.set_resizable(true);
+    }
+    let _ = app.emit("window-cinema-changed", enabled);
+}
+
+#[tauri::command]
+async fn window_set_cinema_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    info!("Cinema mode {}", if enabled { "on" } else { "off" });
+    set_cinema_mode(&app, &win, enabled);
+    remember_layout_flag(&app, "cinema", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+async fn window_is_cinema_mode(app: AppHandle) -> Result<bool, String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    Ok(read_layout_window_state(&store, app.state::<LayoutState>().current()).cinema)
+}
+
+// Start a native window move; called on mousedown in a drag region
+#[tauri::command]
+async fn window_start_drag(app: AppHandle) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    if win.is_fullscreen().unwrap_or(false) {
+        return Ok(());
+    }
+    win.start_dragging().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_toggle_maximize(app: AppHandle) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    if win.is_fullscreen().unwrap_or(false) || app.state::<PipState>().active() {
+        return Ok(());
+    }
+    if win.is_maximized().unwrap_or(false) {
+        win.unmaximize().map_err(|e| e.to_string())
+    } else {
+        win.maximize().map_err(|e| e.to_string())
+    }
+}
+
+// ========================================================================
+// Fit Window to Video
+// ========================================================================
+//
+// The video player reports each new video's display size (mpv's
+// video-params/dw and dh, which include the pixel aspect) through
+// window_set_video_size, and clears it when playback stops.
+// window_fit_to_video sizes the window's content area to a multiple of that
+// size, shrunk to the monitor's work area, and centres it there.
+// video.auto_fit_window fits every new video; video.lock_window_aspect keeps
+// manual resizes at the video's aspect ratio.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FitScale {
+    Factor(f64),
+    // As large as the work area allows
+    Screen,
+}
+
+impl FitScale {
+    fn parse(value: &str) -> Option<FitScale> {
+        match value {
+            "fit" => Some(FitScale::Screen),
+            _ => value
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite() && *f > 0.0 && *f <= 4.0)
+                .map(FitScale::Factor),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct VideoSize {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Default)]
+struct VideoFitState {
+    video: Mutex<Option<VideoSize>>,
+    // Content size after the last fit or aspect correction, used to tell
+    // which edge a manual resize moved
+    last_size: Mutex<Option<tauri::PhysicalSize<u32>>>,
+}
+
+fn fit_window_to_video(app: &AppHandle, win: &tauri::WebviewWindow, scale: FitScale) -> Result<(), String> {
+    let state = app.state::<VideoFitState>();
+    let video = state.video.lock().unwrap().ok_or("No video is playing")?;
+    if kiosk_active(app) || app.state::<PipState>().active() || win.is_fullscreen().unwrap_or(false) {
+        debug!("Not fitting window to video outside the normal windowed state");
+        return Ok(());
+    }
+    if win.is_maximized().unwrap_or(false) {
+        let _ = win.unmaximize();
+    }
+    let monitor = win
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| win.primary_monitor().ok().flatten())
+        .ok_or("No monitor available")?;
+
+    // Title bar and borders, zero in cinema mode
+    let inner = win.inner_size().map_err(|e| e.to_string())?;
+    let outer = win.outer_size().map_err(|e| e.to_string())?;
+    let frame_w = outer.width.saturating_sub(inner.width) as f64;
+    let frame_h = outer.height.saturating_sub(inner.height) as f64;
+
+    let area = monitor.work_area();
+    let area_w = area.size.width as f64;
+    let area_h = area.size.height as f64;
+    let (video_w, video_h) = (video.width as f64, video.height as f64);
+    let factor = match scale {
+        FitScale::Factor(factor) => factor,
+        FitScale::Screen => f64::INFINITY,
+    };
+    // Shrink to the work area, keeping the aspect ratio
+    let factor = factor
+        .min((area_w - frame_w).max(1.0) / video_w)
+        .min((area_h - frame_h).max(1.0) / video_h);
+    let size = tauri::PhysicalSize::new(
+        (video_w * factor).round().max(1.0) as u32,
+        (video_h * factor).round().max(1.0) as u32,
+    );
+    let position = tauri::PhysicalPosition::new(
+        (area.position.x as f64 + (area_w - size.width as f64 - frame_w) / 2.0).round() as i32,
+        (area.position.y as f64 + (area_h - size.height as f64 - frame_h) / 2.0).round() as i32,
+    );
+    info!(
+        "Fitting window to {}x{} video: {}x{} at {},{}",
+        video.width, video.height, size.width, size.height, position.x, position.y
+    );
+
+    *state.last_size.lock().unwrap() = Some(size);
+    win.set_size(size).map_err(|e| e.to_string())?;
+    win.set_position(position).map_err(|e| e.to_string())
+}
+
+// Keep a manual resize at the video's aspect ratio, following whichever
+// edge moved more
+fn lock_video_aspect(app: &AppHandle, win: &tauri::WebviewWindow, size: tauri::PhysicalSize<u32>) {
+    let state = app.state::<VideoFitState>();
+    let Some(video) = *state.video.lock().unwrap() else {
+        return;
+    };
+    if size.width == 0
+        || size.height == 0
+        || !effective_setting(app, "video", "lock_window_aspect").as_bool().unwrap_or(false)
+        || app.state::<PipState>().active()
+        || win.is_fullscreen().unwrap_or(false)
+        || win.is_maximized().unwrap_or(false)
+    {
+        return;
+    }
+
+    let aspect = video.width as f64 / video.height as f64;
+    let previous = state.last_size.lock().unwrap().replace(size);
+    let width_moved = previous.is_none_or(|p| size.width.abs_diff(p.width) >= size.height.abs_diff(p.height));
+    let target = if width_moved {
+        tauri::PhysicalSize::new(size.width, (size.width as f64 / aspect).round() as u32)
+    } else {
+        tauri::PhysicalSize::new((size.height as f64 * aspect).round() as u32, size.height)
+    };
+    // A pixel of rounding is fine and avoids feeding our own resize events
+    if target.width.abs_diff(size.width) > 1 || target.height.abs_diff(size.height) > 1 {
+        *state.last_size.lock().unwrap() = Some(target);
+        let _ = win.set_size(target);
+    }
+}
+
+// `size` is the new video's display size, or null when playback stops
+#[tauri::command]
+async fn window_set_video_size(app: AppHandle, size: Option<VideoSize>) -> Result<(), String> {
+    let size = size.filter(|s| s.width > 0 && s.height > 0);
+    let state = app.state::<VideoFitState>();
+    *state.video.lock().unwrap() = size;
+    *state.last_size.lock().unwrap() = None;
+    if size.is_none() {
+        return Ok(());
+    }
+
+    // "off" and unknown values don't parse
+    let auto = effective_setting(&app, "video", "auto_fit_window");
+    match auto.as_str().and_then(FitScale::parse) {
+        Some(scale) => {
+            let win = app.get_webview_window("main").ok_or("Main window not found")?;
+            fit_window_to_video(&app, &win, scale)
+        }
+        None => Ok(()),
+    }
+}
+
+// `scale` is "0.5", "1", "2" or "fit" (the largest size the screen allows)
+#[tauri::command]
+async fn window_fit_to_video(app: AppHandle, scale: String) -> Result<(), String> {
+    let fit = FitScale::parse(&scale).ok_or_else(|| format!("Unknown scale '{}'", scale))?;
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    fit_window_to_video(&app, &win, fit)
+}
+
+// ========================================================================
+// Window Geometry
+// ========================================================================
+//
+// Windowed geometry is stored under a key prefix (state.geometry for the
+// desktop layout, see LayoutMode::geometry_prefix) in logical pixels, so a
+// scale change keeps the window the same visual size:
+//   <prefix>.monitor    name of the monitor the window was on
+//   <prefix>.x / .y     offset from that monitor's top-left corner
+//   <prefix>.w / .h     inner size
+//   <prefix>.maximized
+// On restore the rectangle is placed on the named monitor if it is
+// connected, otherwise centred on the primary monitor, and always clamped to
+// the monitor's work area.
+
+const MIN_WINDOW_WIDTH: f64 = 200.0;
+const MIN_WINDOW_HEIGHT: f64 = 150.0;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SavedGeometry {
+    monitor: Option<String>,
+    position: Option<(f64, f64)>,
+    size: Option<(f64, f64)>,
+    maximized: bool,
+}
+
+fn read_geometry<R: tauri::Runtime>(store: &tauri_plugin_store::Store<R>, prefix: &str) -> SavedGeometry {
+    let get_f64 = |key: &str| store.get(format!("{}.{}", prefix, key)).and_then(|v| v.as_f64());
+    SavedGeometry {
+        monitor: store
+            .get(format!("{}.monitor", prefix))
+            .and_then(|v| v.as_str().map(String::from)),
+        position: get_f64("x").zip(get_f64("y")),
+        size: get_f64("w").zip(get_f64("h")),
+        maximized: store
+            .get(format!("{}.maximized", prefix))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    }
+}
+
+fn write_geometry<R: tauri::Runtime>(store: &tauri_plugin_store::Store<R>, prefix: &str, geometry: &SavedGeometry) {
+    match &geometry.monitor {
+        Some(name) => store.set(format!("{}.monitor", prefix), serde_json::json!(name)),
+        None => {
+            store.delete(format!("{}.monitor", prefix));
+        }
+    }
+    if let Some((x, y)) = geometry.position {
+        store.set(format!("{}.x", prefix), serde_json::json!(x.round() as i64));
+        store.set(format!("{}.y", prefix), serde_json::json!(y.round() as i64));
+    }
+    if let Some((w, h)) = geometry.size {
+        store.set(format!("{}.w", prefix), serde_json::json!(w.round() as i64));
+        store.set(format!("{}.h", prefix), serde_json::json!(h.round() as i64));
+    }
+    store.set(format!("{}.maximized", prefix), serde_json::json!(geometry.maximized));
+}
+
+// Current windowed geometry, relative to the monitor the window is on
+fn capture_geometry(win: &tauri::WebviewWindow) -> Result<SavedGeometry, String> {
+    let scale = win.scale_factor().map_err(|e| e.to_string())?;
+    let pos = win.outer_position().map_err(|e| e.to_string())?;
+    let size = win.inner_size().map_err(|e| e.to_string())?;
+    let monitor = win.current_monitor().map_err(|e| e.to_string())?;
+    let origin = monitor
+        .as_ref()
+        .map(|m| *m.position())
+        .unwrap_or(tauri::PhysicalPosition::new(0, 0));
+    Ok(SavedGeometry {
+        monitor: monitor.as_ref().and_then(|m| m.name().cloned()),
+        position: Some((
+            (pos.x - origin.x) as f64 / scale,
+            (pos.y - origin.y) as f64 / scale,
+        )),
+        size: Some((size.width as f64 / scale, size.height as f64 / scale)),
+        maximized: win.is_maximized().unwrap_or(false),
+    })
+}
+
+fn apply_geometry(win: &tauri::WebviewWindow, saved: &SavedGeometry) {
+    let monitors = win.available_monitors().unwrap_or_default();
+    let named = saved
+        .monitor
+        .as_ref()
+        .and_then(|name| monitors.iter().find(|m| m.name() == Some(name)).cloned());
+    let monitor_missing = saved.monitor.is_some() && named.is_none();
+    let Some(monitor) = named
+        .or_else(|| win.primary_monitor().ok().flatten())
+        .or_else(|| monitors.first().cloned())
+    else {
+        warn!("No monitors reported, skipping geometry restore");
+        return;
+    };
+
+    if let Some((w, h)) = saved.size {
+        let scale = monitor.scale_factor();
+        let area = monitor.work_area();
+        let area_w = area.size.width as f64;
+        let area_h = area.size.height as f64;
+        // Never larger than the work area, never smaller than the minimum
+        let width = (w * scale).clamp((MIN_WINDOW_WIDTH * scale).min(area_w), area_w).round();
+        let height = (h * scale).clamp((MIN_WINDOW_HEIGHT * scale).min(area_h), area_h).round();
+
+        let centred = (
+            area.position.x as f64 + (area_w - width) / 2.0,
+            area.position.y as f64 + (area_h - height) / 2.0,
+        );
+        let (x, y) = match saved.position {
+            Some((x, y)) if !monitor_missing => {
+                let origin = monitor.position();
+                (origin.x as f64 + x * scale, origin.y as f64 + y * scale)
+            }
+            _ => centred,
+        };
+        // Keep the whole window inside the work area
+        let max_x = area.position.x as f64 + (area_w - width).max(0.0);
+        let max_y = area.position.y as f64 + (area_h - height).max(0.0);
+        let x = x.clamp(area.position.x as f64, max_x).round() as i32;
+        let y = y.clamp(area.position.y as f64, max_y).round() as i32;
+
+        if monitor_missing {
+            info!(
+                "Saved monitor {:?} is not connected, centring on {:?}",
+                saved.monitor,
+                monitor.name()
+            );
+        }
+        info!(
+            "Restoring window geometry: {}x{} at ({}, {}) on {:?}, maximized={}",
+            width, height, x, y, monitor.name(), saved.maximized
+        );
+        let _ = win.set_size(tauri::PhysicalSize::new(width as u32, height as u32));
+        let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
+    }
+    if saved.maximized {
+        info!("Restoring maximized state");
+        let _ = win.maximize();
+    }
+}
+
+// ========================================================================
+// Fullscreen Monitor
+// ========================================================================
+//
+// main.fullscreenMonitor names the monitor fullscreen playback should use
+// (blank = whichever monitor the window is on). Entering fullscreen moves
+// the window there first; leaving it restores the windowed geometry from
+// before the switch.
+
+#[derive(Default)]
+struct FullscreenState {
+    // Windowed geometry from before the last switch to fullscreen
+    windowed: Mutex<Option<SavedGeometry>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorInfo {
+    name: Option<String>,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+    // From the display mode backend; Tauri's monitor API has no refresh rate
+    refresh_rate: Option<f64>,
+    primary: bool,
+    current: bool,
+}
+
+fn preferred_fullscreen_monitor(app: &AppHandle, win: &tauri::WebviewWindow) -> Option<tauri::Monitor> {
+    let store = app.store("settings.json").ok()?;
+    let policy = app.state::<policy::Policy>();
+    let server_id = app.state::<ServerState>().active_id();
+    let (value, _) = resolve_setting(&store, &policy, server_id.as_deref(), "main", "fullscreenMonitor");
+    let name = value.as_str().filter(|name| !name.is_empty())?.to_string();
+    let monitor = win
+        .available_monitors()
+        .ok()?
+        .into_iter()
+        .find(|m| m.name() == Some(&name));
+    if monitor.is_none() {
+        warn!("Preferred fullscreen monitor {:?} is not connected", name);
+    }
+    monitor
+}
+
+fn set_main_fullscreen(app: &AppHandle, win: &tauri::WebviewWindow, fullscreen: bool) -> Result<(), String> {
+    let was_fullscreen = win.is_fullscreen().unwrap_or(false);
+    let state = app.state::<FullscreenState>();
+
+    if !fullscreen {
+        win.set_fullscreen(false).map_err(|e| e.to_string())?;
+        if was_fullscreen {
+            if let Some(geometry) = state.windowed.lock().unwrap().take() {
+                apply_geometry(win, &geometry);
+            }
+        }
+        return Ok(());
+    }
+
+    if !was_fullscreen {
+        *state.windowed.lock().unwrap() = capture_geometry(win).ok();
+        if let Some(target) = preferred_fullscreen_monitor(app, win) {
+            let current = win.current_monitor().ok().flatten();
+            if current.as_ref().and_then(|m| m.name()) != target.name() {
+                info!("Moving window to {:?} for fullscreen", target.name());
+                if win.is_maximized().unwrap_or(false) {
+                    let _ = win.unmaximize();
+                }
+                let _ = win.set_position(*target.position());
+            }
+        }
+    }
+    win.set_fullscreen(true).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn window_list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    let primary = win.primary_monitor().ok().flatten();
+    let current = win.current_monitor().ok().flatten();
+    let same = |a: &tauri::Monitor, b: &Option<tauri::Monitor>| {
+        b.as_ref()
+            .is_some_and(|b| a.name() == b.name() && a.position() == b.position())
+    };
+    let monitors = win.available_monitors().map_err(|e| e.to_string())?;
+    let outputs = app
+        .state::<DisplayState>()
+        .switcher
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|switcher| switcher.outputs().ok())
+        .unwrap_or_default();
+    let refresh_rate = |m: &tauri::Monitor| {
+        outputs
+            .iter()
+            .find(|o| Some(&o.name) == m.name())
+            .and_then(|o| o.current.as_ref())
+            .map(|mode| mode.refresh_rate)
+    };
+    Ok(monitors
+        .iter()
+        .map(|m| MonitorInfo {
+            name: m.name().cloned(),
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+            scale_factor: m.scale_factor(),
+            refresh_rate: refresh_rate(m),
+            primary: same(m, &primary),
+            current: same(m, &current),
+        })
+        .collect())
+}
+
+// ========================================================================
+// Picture-in-Picture
+// ========================================================================
+//
+// A small borderless, always-on-top window pinned to a corner of the work
+// area. Its height follows the video aspect ratio, and after the user drags
+// or resizes it the window settles into the nearest corner. The PiP
+// geometry is kept apart from the windowed one:
+//   state.pip.monitor   monitor the mini player was last on
+//   state.pip.corner    top-left, top-right, bottom-left or bottom-right
+//   state.pip.w         width in logical pixels
+// Leaving PiP restores the geometry, decorations, always-on-top and
+// fullscreen state from before it was entered.
+
+const PIP_PREFIX: &str = "state.pip";
+const PIP_DEFAULT_WIDTH: f64 = 400.0;
+const PIP_MIN_WIDTH: f64 = 200.0;
+const PIP_MARGIN: f64 = 16.0;
+// Quiet period after the last move/resize before snapping to a corner
+const PIP_SETTLE_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum PipCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl PipCorner {
+    fn as_str(self) -> &'static str {
+        match self {
+            PipCorner::TopLeft => "top-left",
+            PipCorner::TopRight => "top-right",
+            PipCorner::BottomLeft => "bottom-left",
+            PipCorner::BottomRight => "bottom-right",
+        }
+    }
+
+    fn parse(value: &str) -> Option<PipCorner> {
+        match value {
+            "top-left" => Some(PipCorner::TopLeft),
+            "top-right" => Some(PipCorner::TopRight),
+            "bottom-left" => Some(PipCorner::BottomLeft),
+            "bottom-right" => Some(PipCorner::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+// Window state from before entering PiP
+struct PipRestore {
+    geometry: SavedGeometry,
+    decorated: bool,
+    always_on_top: bool,
+    fullscreen: bool,
+}
+
+struct PipState {
+    restore: Mutex<Option<PipRestore>>,
+    // Video width / height
+    aspect: Mutex<f64>,
+    // Bumped on every move/resize; only the latest settle task runs
+    settle_generation: std::sync::atomic::AtomicU64,
+}
+
+impl Default for PipState {
+    fn default() -> Self {
+        PipState {
+            restore: Mutex::new(None),
+            aspect: Mutex::new(16.0 / 9.0),
+            settle_generation: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+}
+
+impl PipState {
+    fn active(&self) -> bool {
+        self.restore.lock().unwrap().is_some()
+    }
+}
+
+// The named monitor if connected, else the one the window is on
+fn pip_monitor(win: &tauri::WebviewWindow, name: Option<&str>) -> Option<tauri::Monitor> {
+    name.and_then(|name| {
+        win.available_monitors()
+            .ok()?
+            .into_iter()
+            .find(|m| m.name().map(String::as_str) == Some(name))
+    })
+    .or_else(|| win.current_monitor().ok().flatten())
+    .or_else(|| win.primary_monitor().ok().flatten())
+}
+
+// Size the window for `aspect` and put it in `corner` of the monitor's work
+// area. Returns the logical width actually used.
+fn place_pip(win: &tauri::WebviewWindow, monitor: &tauri::Monitor, corner: PipCorner, width: f64, aspect: f64) -> f64 {
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+    let area_w = area.size.width as f64;
+    let area_h = area.size.height as f64;
+    // At most half the work area in either direction
+    let max_w = (area_w / 2.0).min(area_h / 2.0 * aspect);
+    let width = (width * scale).clamp((PIP_MIN_WIDTH * scale).min(max_w), max_w).round();
+    let height = (width / aspect).round();
+    let margin = PIP_MARGIN * scale;
+    let x = match corner {
+        PipCorner::TopLeft | PipCorner::BottomLeft => area.position.x as f64 + margin,
+        PipCorner::TopRight | PipCorner::BottomRight => area.position.x as f64 + area_w - width - margin,
+    };
+    let y = match corner {
+        PipCorner::TopLeft | PipCorner::TopRight => area.position.y as f64 + margin,
+        PipCorner::BottomLeft | PipCorner::BottomRight => area.position.y as f64 + area_h - height - margin,
+    };
+    let size = tauri::PhysicalSize::new(width as u32, height as u32);
+    let position = tauri::PhysicalPosition::new(x.round() as i32, y.round() as i32);
+
+    // Skip no-op updates so settling doesn't feed its own move events
+    if win.outer_size().ok() != Some(size) {
+        let _ = win.set_size(size);
+    }
+    if win.outer_position().ok() != Some(position) {
+        let _ = win.set_position(position);
+    }
+    width / scale
+}
+
+fn save_pip_geometry(app: &AppHandle, monitor: &tauri::Monitor, corner: PipCorner, width: f64) {
+    let Ok(store) = app.store("settings.json") else {
+        return;
+    };
+    match monitor.name() {
+        Some(name) => store.set(format!("{}.monitor", PIP_PREFIX), serde_json::json!(name)),
+        None => {
+            store.delete(format!("{}.monitor", PIP_PREFIX));
+        }
+    }
+    store.set(format!("{}.corner", PIP_PREFIX), serde_json::json!(corner.as_str()));
+    store.set(format!("{}.w", PIP_PREFIX), serde_json::json!(width.round() as i64));
+}
+
+// Snap to the corner nearest the window centre, re-apply the aspect ratio
+// and remember the result
+fn settle_pip(app: &AppHandle, win: &tauri::WebviewWindow) {
+    if !app.state::<PipState>().active() {
+        return;
+    }
+    let (Ok(pos), Ok(size), Some(monitor)) = (win.outer_position(), win.outer_size(), pip_monitor(win, None))
+    else {
+        return;
+    };
+    let area = monitor.work_area();
+    let centre_x = pos.x as f64 + size.width as f64 / 2.0;
+    let centre_y = pos.y as f64 + size.height as f64 / 2.0;
+    let left = centre_x < area.position.x as f64 + area.size.width as f64 / 2.0;
+    let top = centre_y < area.position.y as f64 + area.size.height as f64 / 2.0;
+    let corner = match (top, left) {
+        (true, true) => PipCorner::TopLeft,
+        (true, false) => PipCorner::TopRight,
+        (false, true) => PipCorner::BottomLeft,
+        (false, false) => PipCorner::BottomRight,
+    };
+    let aspect = *app.state::<PipState>().aspect.lock().unwrap();
+    let width = place_pip(win, &monitor, corner, size.width as f64 / monitor.scale_factor(), aspect);
+    save_pip_geometry(app, &monitor, corner, width);
+}
+
+// Debounced settle_pip, called for every move/resize while in PiP
+fn schedule_pip_settle(app: &AppHandle) {
+    let state = app.state::<PipState>();
+    if !state.active() {
+        return;
+    }
+    let generation = state.settle_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PIP_SETTLE_DELAY).await;
+        if app.state::<PipState>().settle_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Some(win) = app.get_webview_window("main") {
+            settle_pip(&app, &win);
+        }
+    });
+}
+
+fn set_picture_in_picture(
+    app: &AppHandle,
+    win: &tauri::WebviewWindow,
+    enabled: bool,
+    aspect: Option<f64>,
+) -> Result<(), String> {
+    let state = app.state::<PipState>();
+    if let Some(aspect) = aspect.filter(|a| a.is_finite() && *a > 0.1 && *a < 10.0) {
+        *state.aspect.lock().unwrap() = aspect;
+    }
 
-    let mut result = serde_json::Map::new();
-    for (key, value) in store.entries() {
-        if let Some(setting_key) = key.strip_prefix(&prefix) {
-            result.insert(setting_key.to_string(), value.clone());
+    if !enabled {
+        let Some(restore) = state.restore.lock().unwrap().take() else {
+            return Ok(());
+        };
+        info!("Leaving picture-in-picture");
+        let _ = win.set_decorations(restore.decorated);
+        set_main_always_on_top(win, restore.always_on_top)?;
+        apply_geometry(win, &restore.geometry);
+        if restore.fullscreen {
+            set_main_fullscreen(app, win, true)?;
         }
+        let _ = app.emit("window-pip-changed", false);
+        return Ok(());
     }
 
-    debug!("settings_get_all: {} returned {} keys", section, result.len());
-    Ok(Value::Object(result))
-}
-
-// ========================================================================
-// Window Commands
-// ========================================================================
+    if state.active() {
+        // Already in PiP: only the aspect ratio may have changed
+        settle_pip(app, win);
+        return Ok(());
+    }
+    if kiosk_active(app) {
+        return Err("Picture-in-picture is not available in kiosk mode".to_string());
+    }
 
-#[tauri::command]
-async fn window_set_title(app: AppHandle, title: String) -> Result<(), String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or("Main window not found")?;
-    window.set_title(&title).map_err(|e| e.to_string())
-}
+    let fullscreen = win.is_fullscreen().unwrap_or(false);
+    if fullscreen {
+        set_main_fullscreen(app, win, false)?;
+    }
+    let maximized = win.is_maximized().unwrap_or(false);
+    if maximized {
+        let _ = win.unmaximize();
+    }
+    let mut geometry = capture_geometry(win)?;
+    geometry.maximized = maximized;
+    *state.restore.lock().unwrap() = Some(PipRestore {
+        geometry,
+        decorated: win.is_decorated().unwrap_or(true),
+        always_on_top: win.is_always_on_top().unwrap_or(false),
+        fullscreen,
+    });
 
-#[tauri::command]
-async fn window_set_fullscreen(app: AppHandle, fullscreen: bool) -> Result<(), String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or("Main window not found")?;
-    window.set_fullscreen(fullscreen).map_err(|e| e.to_string())
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    let saved_monitor = store
+        .get(format!("{}.monitor", PIP_PREFIX))
+        .and_then(|v| v.as_str().map(String::from));
+    let corner = store
+        .get(format!("{}.corner", PIP_PREFIX))
+        .and_then(|v| v.as_str().and_then(PipCorner::parse))
+        .unwrap_or_default();
+    let width = store
+        .get(format!("{}.w", PIP_PREFIX))
+        .and_then(|v| v.as_f64())
+        .unwrap_or(PIP_DEFAULT_WIDTH);
+    let monitor = pip_monitor(win, saved_monitor.as_deref()).ok_or("No monitor available")?;
+
+    info!(
+        "Entering picture-in-picture: {} corner of {:?}, width {}",
+        corner.as_str(),
+        monitor.name(),
+        width
+    );
+    let _ = win.set_decorations(false);
+    set_main_always_on_top(win, true)?;
+    let aspect = *state.aspect.lock().unwrap();
+    place_pip(win, &monitor, corner, width, aspect);
+    let _ = app.emit("window-pip-changed", true);
+    Ok(())
 }
 
+// `aspect` is the video width / height; 16:9 when not given
 #[tauri::command]
-async fn window_is_fullscreen(app: AppHandle) -> Result<bool, String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or("Main window not found")?;
-    window.is_fullscreen().map_err(|e| e.to_string())
+async fn window_set_picture_in_picture(app: AppHandle, enabled: bool, aspect: Option<f64>) -> Result<(), String> {
+    let win = app.get_webview_window("main").ok_or("Main window not found")?;
+    set_picture_in_picture(&app, &win, enabled, aspect)
 }
 
 #[tauri::command]
-async fn window_set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), String> {
-    info!("Setting always-on-top: {}", enabled);
-    let win = app.get_webview_window("main").ok_or("Main window not found")?;
-    win.set_always_on_top(enabled).map_err(|e| e.to_string())
+async fn window_is_picture_in_picture(state: State<'_, PipState>) -> Result<bool, String> {
+    Ok(state.active())
 }
 
-#[tauri::command]
-async fn window_is_always_on_top(app: AppHandle) -> Result<bool, String> {
-    let win = app.get_webview_window("main").ok_or("Main window not found")?;
-    win.is_always_on_top().map_err(|e| e.to_string())
+// ========================================================================
+// Display Mode
+// ========================================================================
+//
+// With video.refresh_rate_switching on, the player asks for the output the
+// window is on to be switched to a refresh rate matching the video frame
+// rate. The original mode is restored when playback stops and on exit.
+
+#[derive(Default)]
+struct DisplayState {
+    // None when no backend is available on this system
+    switcher: Mutex<Option<display::DisplayModeSwitcher>>,
 }
 
 #[tauri::command]
-async fn window_raise(app: AppHandle) -> Result<(), String> {
-    debug!("Raising main window");
-    let win = app.get_webview_window("main").ok_or("Main window not found")?;
-    win.set_focus().map_err(|e| e.to_string())
+async fn display_list_outputs(state: State<'_, DisplayState>) -> Result<Vec<display::Output>, String> {
+    match state.switcher.lock().unwrap().as_mut() {
+        Some(switcher) => switcher.outputs().map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    }
 }
 
+// Returns true when the display mode was changed
 #[tauri::command]
-async fn window_set_cursor_visible(app: AppHandle, visible: bool) -> Result<(), String> {
-    let win = app.get_webview_window("main").ok_or("Main window not found")?;
-    win.set_cursor_visible(visible).map_err(|e| e.to_string())
+async fn display_match_refresh_rate(app: AppHandle, fps: f64) -> Result<bool, String> {
+    let output = app
+        .get_webview_window("main")
+        .and_then(|win| win.current_monitor().ok().flatten())
+        .and_then(|m| m.name().cloned());
+    let state = app.state::<DisplayState>();
+    let mut switcher = state.switcher.lock().unwrap();
+    let Some(switcher) = switcher.as_mut() else {
+        debug!("Display mode switching unavailable; ignoring {} fps", fps);
+        return Ok(false);
+    };
+    switcher.match_fps(output.as_deref(), fps).map_err(|e| e.to_string())
 }
 
+// Returns true when a previously switched mode was restored
 #[tauri::command]
-async fn window_save_geometry(app: AppHandle) -> Result<(), String> {
-    let win = app.get_webview_window("main").ok_or("Main window not found")?;
-    let store = app.store("settings.json").map_err(|e| e.to_string())?;
-
-    // Don't save geometry while fullscreen — we want the windowed geometry
-    if win.is_fullscreen().unwrap_or(false) {
-        debug!("Skipping geometry save while fullscreen");
-        return Ok(());
+async fn display_restore(state: State<'_, DisplayState>) -> Result<bool, String> {
+    match state.switcher.lock().unwrap().as_mut() {
+        Some(switcher) => switcher.restore().map_err(|e| e.to_string()),
+        None => Ok(false),
     }
-
-    let maximized = win.is_maximized().unwrap_or(false);
-    if maximized {
-        // Only save the maximized flag, keep prior windowed position/size
-        store.set("state.geometry.maximized", serde_json::json!(true));
-        debug!("Saved geometry: maximized=true");
-    } else {
-        let pos = win.outer_position().map_err(|e| e.to_string())?;
-        let size = win.outer_size().map_err(|e| e.to_string())?;
-        store.set("state.geometry.x", serde_json::json!(pos.x));
-        store.set("state.geometry.y", serde_json::json!(pos.y));
-        store.set("state.geometry.w", serde_json::json!(size.width));
-        store.set("state.geometry.h", serde_json::json!(size.height));
-        store.set("state.geometry.maximized", serde_json::json!(false));
-        debug!("Saved geometry: {}x{} at ({}, {})", size.width, size.height, pos.x, pos.y);
-    }
-
-    Ok(())
 }
 
 // ========================================================================
@@ -359,24 +2231,28 @@ async fn system_hello(name: String) {
 }
 
 #[tauri::command]
-async fn system_open_external_url(url: String) -> Result<(), String> {
-    info!("Opening external URL: {}", url);
-    open::that(&url).map_err(|e| {
-        error!("Failed to open external URL: {}", e);
-        e.to_string()
-    })
-}
-
-#[tauri::command]
-async fn system_exit(app: AppHandle) {
+async fn system_exit(
+    app: AppHandle,
+    webview: tauri::Webview,
+    pin: Option<String>,
+) -> Result<(), policy::PolicyError> {
+    authorize_ipc(&app, &webview, "system_exit")?;
+    check_kiosk_pin(&app, "exit", pin.as_deref())?;
     info!("Application exit requested");
     app.exit(0);
+    Ok(())
 }
 
 #[tauri::command]
-fn system_restart(app: AppHandle) {
+fn system_restart(
+    app: AppHandle,
+    webview: tauri::Webview,
+    pin: Option<String>,
+) -> Result<(), policy::PolicyError> {
+    authorize_ipc(&app, &webview, "system_restart")?;
+    check_kiosk_pin(&app, "restart", pin.as_deref())?;
     info!("Application restart requested");
-    app.restart();
+    app.restart()
 }
 
 #[tauri::command]
@@ -397,7 +2273,18 @@ fn system_debug_info(app: AppHandle) -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn system_check_for_updates(app: AppHandle) -> Result<(), String> {
+async fn system_check_for_updates(
+    app: AppHandle,
+    servers: State<'_, ServerState>,
+    policy: State<'_, policy::Policy>,
+) -> Result<(), String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    let server_id = servers.active_id();
+    let (enabled, _) = resolve_setting(&store, &policy, server_id.as_deref(), "main", "checkForUpdates");
+    if enabled == Value::Bool(false) {
+        info!("Update checks disabled by settings");
+        return Ok(());
+    }
     info!("Checking for updates");
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
@@ -434,6 +2321,353 @@ fn system_network_addresses() -> Vec<String> {
     addresses
 }
 
+// ========================================================================
+// External URLs
+// ========================================================================
+//
+// Links leave the app through the OS default handler, which will launch
+// whatever is registered for a scheme (file://, smb://, custom protocol
+// handlers). Schemes listed in main.externalUrlSchemes open directly; any
+// other scheme is held until the user confirms it in a native dialog, which
+// page scripts can neither answer nor suppress. The webview may not edit
+// the allowlist (see check_protected_settings_write), and kiosk mode opens
+// no external URLs at all.
+
+fn allowed_external_schemes(app: &AppHandle) -> Vec<String> {
+    let Ok(store) = app.store("settings.json") else {
+        return Vec::new();
+    };
+    let policy = app.state::<policy::Policy>();
+    let server_id = app.state::<ServerState>().active_id();
+    let (value, _) = resolve_setting(&store, &policy, server_id.as_deref(), "main", "externalUrlSchemes");
+    value
+        .as_str()
+        .unwrap_or_default()
+        .split(',')
+        .map(|scheme| scheme.trim().trim_end_matches(':').to_ascii_lowercase())
+        .filter(|scheme| !scheme.is_empty())
+        .collect()
+}
+
+fn parse_external_url(url: &str) -> Result<tauri::Url, String> {
+    let parsed: tauri::Url = url.trim().parse().map_err(|e| format!("Invalid URL: {}", e))?;
+    // "C:\path\app.exe" parses with the drive letter as its scheme
+    if parsed.scheme().len() < 2 {
+        return Err(format!("Not a URL: {}", url));
+    }
+    Ok(parsed)
+}
+
+fn launch_external_url(url: &tauri::Url) {
+    let target = url.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = open::that(&target) {
+            error!("Failed to open external URL {}: {}", target, e);
+        }
+    });
+}
+
+// Open `url` now if its scheme is allowed, otherwise ask the user in a
+// native dialog. `source` says what asked for it, for the log.
+fn open_external_url(app: &AppHandle, url: tauri::Url, source: &str) {
+    let scheme = url.scheme().to_string();
+    if allowed_external_schemes(app).contains(&scheme) {
+        info!("External URL {} ({}): allowed, scheme '{}' is in the allowlist", url, source, scheme);
+        launch_external_url(&url);
+        return;
+    }
+
+    info!(
+        "External URL {} ({}): scheme '{}' is not in the allowlist, asking for confirmation",
+        url, source, scheme
+    );
+    let mut dialog = app
+        .dialog()
+        .message(format!(
+            "Open this {}: link with an external application?\n\n{}",
+            scheme, url
+        ))
+        .title("Open external link")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Open".to_string(),
+            "Cancel".to_string(),
+        ));
+    if let Some(window) = app.get_webview_window("main") {
+        dialog = dialog.parent(&window);
+    }
+    dialog.show(move |approved| {
+        if approved {
+            info!("External URL {}: approved by the user", url);
+            launch_external_url(&url);
+        } else {
+            info!("External URL {}: declined by the user", url);
+        }
+    });
+}
+
+#[tauri::command]
+async fn system_open_external_url(
+    app: AppHandle,
+    webview: tauri::Webview,
+    url: String,
+) -> Result<(), policy::PolicyError> {
+    authorize_ipc(&app, &webview, "system_open_external_url")?;
+    if kiosk_active(&app) {
+        warn!("Kiosk mode: blocked external URL {:?}", url);
+        return Err("External links are disabled in kiosk mode".to_string().into());
+    }
+    let parsed = parse_external_url(&url).map_err(|e| {
+        warn!("External URL {:?}: rejected, {}", url, e);
+        e
+    })?;
+    open_external_url(&app, parsed, "system_open_external_url");
+    Ok(())
+}
+
+// ========================================================================
+// Kiosk Mode
+// ========================================================================
+//
+// Active when launched with --kiosk, or while main.forceAlwaysFS is on
+// (usually locked by the admin policy). The window is kept fullscreen, close
+// requests are ignored, and the webview may only show the configured server
+// and the local connect page. Leaving is gated on the policy's kioskPin:
+// exit, restart and settings writes that would turn forceAlwaysFS off need
+// it. Without a kioskPin, a kiosk the setting turned on can be left freely,
+// and one started with --kiosk cannot be left at all.
+
+const KIOSK_MAX_PIN_ATTEMPTS: u32 = 3;
+const KIOSK_PIN_LOCKOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Default)]
+struct KioskState {
+    // --kiosk on the command line
+    forced: bool,
+    // Wrong PIN count, and the end of the current lockout
+    failed_pins: Mutex<(u32, Option<std::time::Instant>)>,
+}
+
+fn kiosk_active(app: &AppHandle) -> bool {
+    let Some(kiosk) = app.try_state::<KioskState>() else {
+        return false;
+    };
+    if kiosk.forced {
+        return true;
+    }
+    let policy = app.state::<policy::Policy>();
+    let Ok(store) = app.store("settings.json") else {
+        return false;
+    };
+    let server_id = app.state::<ServerState>().active_id();
+    let (value, _) = resolve_setting(&store, &policy, server_id.as_deref(), "main", "forceAlwaysFS");
+    value == Value::Bool(true)
+}
+
+// Writes that would turn main.forceAlwaysFS off need the PIN while it is
+// what keeps kiosk mode on
+fn check_kiosk_settings_write(
+    app: &AppHandle,
+    pending: &[(String, String, Value)],
+    pin: Option<&str>,
+) -> Result<(), String> {
+    let turns_off = pending
+        .iter()
+        .any(|(section, key, value)| section == "main" && key == "forceAlwaysFS" && *value != Value::Bool(true));
+    if !turns_off || app.state::<KioskState>().forced {
+        return Ok(());
+    }
+    check_kiosk_pin(app, "turning off kiosk mode", pin)
+}
+
+// Ok when kiosk mode is off or `pin` matches the policy's kioskPin. Three
+// wrong PINs lock further attempts out for 30 seconds.
+fn check_kiosk_pin(app: &AppHandle, action: &str, pin: Option<&str>) -> Result<(), String> {
+    if !kiosk_active(app) {
+        return Ok(());
+    }
+    let policy = app.state::<policy::Policy>();
+    let kiosk = app.state::<KioskState>();
+    if policy.kiosk_pin().is_none() {
+        if kiosk.forced {
+            warn!("Kiosk mode: {} refused, --kiosk without a kioskPin in the policy", action);
+            return Err("Kiosk mode is active and no admin PIN is configured".to_string());
+        }
+        // forceAlwaysFS alone only keeps the window fullscreen
+        return Ok(());
+    }
+
+    let mut failed = kiosk.failed_pins.lock().unwrap();
+    if let Some(until) = failed.1 {
+        if std::time::Instant::now() < until {
+            warn!("Kiosk mode: {} refused, PIN entry locked out", action);
+            return Err("Too many wrong PIN attempts, try again later".to_string());
+        }
+        *failed = (0, None);
+    }
+    if pin.is_some_and(|pin| policy.kiosk_pin_matches(pin)) {
+        *failed = (0, None);
+        info!("Kiosk mode: {} authorised with admin PIN", action);
+        return Ok(());
+    }
+
+    failed.0 += 1;
+    warn!("Kiosk mode: wrong PIN for {} (attempt {})", action, failed.0);
+    if failed.0 >= KIOSK_MAX_PIN_ATTEMPTS {
+        failed.1 = Some(std::time::Instant::now() + KIOSK_PIN_LOCKOUT);
+    }
+    Err("Wrong admin PIN".to_string())
+}
+
+// Put the window back into fullscreen after the window manager or a key
+// binding took it out. Delayed so the triggering state change settles first.
+fn enforce_kiosk_fullscreen(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        if !kiosk_active(&app) {
+            return;
+        }
+        let Some(win) = app.get_webview_window("main") else {
+            return;
+        };
+        if win.is_minimized().unwrap_or(false) {
+            let _ = win.unminimize();
+        }
+        if !win.is_fullscreen().unwrap_or(true) {
+            info!("Kiosk mode: restoring fullscreen");
+            let _ = set_main_fullscreen(&app, &win, true);
+        }
+    });
+}
+
+// The server the kiosk is pinned to: the policy's serverUrl, else the saved one
+fn configured_server_url(app: &AppHandle) -> Option<tauri::Url> {
+    let policy = app.state::<policy::Policy>();
+    let url = match policy.server_url() {
+        Some(url) => url.to_string(),
+        None => app
+            .store("settings.json")
+            .ok()?
+            .get("server_url")?
+            .as_str()?
+            .to_string(),
+    };
+    url.parse().ok()
+}
+
+#[tauri::command]
+fn kiosk_status(app: AppHandle, policy: State<'_, policy::Policy>, kiosk: State<'_, KioskState>) -> Value {
+    serde_json::json!({
+        "active": kiosk_active(&app),
+        "forced": kiosk.forced,
+        "pinConfigured": policy.kiosk_pin().is_some(),
+    })
+}
+
+// ========================================================================
+// Navigation Guard
+// ========================================================================
+//
+// The main webview can invoke every command, so it may only show the local
+// connect page and the active server. Other links go through the external
+// URL allowlist and are opened by the OS. In kiosk mode nothing is opened
+// outside the app and the server is the configured one.
+
+// The bundled connect page: tauri://localhost (http://tauri.localhost on
+// Windows), or the Vite dev server in development builds
+fn is_local_app_url(app: &AppHandle, url: &tauri::Url) -> bool {
+    if url.scheme() == "tauri" || url.host_str() == Some("tauri.localhost") {
+        return true;
+    }
+    tauri::is_dev()
+        && app
+            .config()
+            .build
+            .dev_url
+            .as_ref()
+            .is_some_and(|dev| dev.origin() == url.origin())
+}
+
+fn navigation_allowed(app: &AppHandle, url: &tauri::Url) -> bool {
+    if url.scheme() == "about" || is_local_app_url(app, url) {
+        return true;
+    }
+    let kiosk = kiosk_active(app);
+    let server = if kiosk {
+        configured_server_url(app)
+    } else {
+        app.state::<ServerState>().active_url()
+    };
+    if server.is_some_and(|server| server.origin() == url.origin()) {
+        return true;
+    }
+
+    if kiosk {
+        warn!("Kiosk mode: blocked navigation to {}", url);
+    } else {
+        info!("Blocked navigation to {}, handing it to the OS", url);
+        open_external_url(app, url.clone(), "navigation");
+    }
+    false
+}
+
+// ========================================================================
+// IPC Authorization
+// ========================================================================
+//
+// The capability file grants IPC to any http(s) origin because the server
+// URL is only known at runtime. Privileged commands therefore check the
+// calling webview's current URL themselves: only the local connect page and
+// the active server may call them. Rejections are written to the log, at
+// most once per origin and command every 10 seconds.
+
+const IPC_AUDIT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Default)]
+struct IpcAudit {
+    // (origin, command) -> (last logged, rejections since then)
+    rejected: Mutex<HashMap<(String, String), (std::time::Instant, u32)>>,
+}
+
+impl IpcAudit {
+    fn record(&self, origin: String, command: &str) {
+        let now = std::time::Instant::now();
+        let key = (origin, command.to_string());
+        let mut rejected = self.rejected.lock().unwrap();
+        // Expired entries would otherwise pile up for every origin ever seen;
+        // report what they suppressed before dropping them
+        rejected.retain(|(origin, command), (last, suppressed)| {
+            if now.duration_since(*last) < IPC_AUDIT_INTERVAL {
+                return true;
+            }
+            if *suppressed > 0 {
+                warn!("IPC audit: rejected {} from {} {} more times", command, origin, suppressed);
+            }
+            false
+        });
+        match rejected.get_mut(&key) {
+            Some((_, suppressed)) => *suppressed += 1,
+            None => {
+                warn!("IPC audit: rejected {} from {}", key.1, key.0);
+                rejected.insert(key, (now, 0));
+            }
+        }
+    }
+}
+
+fn authorize_ipc(app: &AppHandle, webview: &tauri::Webview, command: &str) -> Result<(), policy::PolicyError> {
+    let url = webview.url().map_err(|e| e.to_string())?;
+    if is_local_app_url(app, &url) {
+        return Ok(());
+    }
+    let servers = app.state::<ServerState>();
+    if servers.active_url().is_some_and(|server| server.origin() == url.origin()) {
+        return Ok(());
+    }
+    app.state::<IpcAudit>().record(url.origin().ascii_serialization(), command);
+    Err(policy::PolicyError::Forbidden(command.to_string()))
+}
+
 // ========================================================================
 // Power Commands
 // ========================================================================
@@ -527,6 +2761,216 @@ async fn power_set_screensaver_enabled(enabled: bool) -> Result<(), String> {
     Ok(())
 }
 
+// ========================================================================
+// Auto Pause
+// ========================================================================
+//
+// Feeds window events (minimized, focus lost) and, on Linux, screen lock
+// signals into the autopause engine, which pauses and resumes playback
+// through `media-control-event`. Playback state and media type come from
+// the media_notify_* commands.
+
+#[derive(Default)]
+struct AutoPauseState {
+    engine: Mutex<autopause::AutoPause>,
+}
+
+fn autopause_update(app: &AppHandle, trigger: autopause::Trigger, active: bool) {
+    let action = app.state::<AutoPauseState>().engine.lock().unwrap().update(trigger, active, |t, media| {
+        effective_setting(app, "autopause", &autopause::setting_key(t, media))
+            .as_bool()
+            .unwrap_or(false)
+    });
+    match action {
+        Some(autopause::Action::Pause) => {
+            info!("Auto pause: pausing ({:?})", trigger);
+            let _ = app.emit("media-control-event", "pause");
+        }
+        Some(autopause::Action::Resume) => {
+            info!("Auto pause: resuming ({:?} cleared)", trigger);
+            let _ = app.emit("media-control-event", "play");
+        }
+        None => {}
+    }
+}
+
+// Minimized and focus state of the main window. Focus loss doesn't count in
+// picture-in-picture, where working in other windows is the point.
+fn autopause_window_changed(app: &AppHandle, focused: Option<bool>) {
+    let Some(win) = app.get_webview_window("main") else {
+        return;
+    };
+    let minimized = win.is_minimized().unwrap_or(false);
+    autopause_update(app, autopause::Trigger::Minimized, minimized);
+    if let Some(focused) = focused {
+        let pip = app.state::<PipState>().active();
+        autopause_update(app, autopause::Trigger::FocusLost, !focused && !pip);
+    }
+}
+
+// ========================================================================
+// System Suspend
+// ========================================================================
+//
+// On Linux a logind delay inhibitor holds off suspend while the page gets
+// ready: PrepareForSleep(true) emits `system-suspending`, the page pauses
+// (which makes jellyfin-web report progress) and answers with
+// power_suspend_ready, then the inhibitor is released. After wake-up a new
+// inhibitor is taken and `system-resumed` lets the player reconnect its
+// stream at the saved position.
+
+// Below logind's default InhibitDelayMaxSec of 5s
+const SUSPEND_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+#[derive(Default)]
+struct SuspendState {
+    ready: AtomicBool,
+}
+
+// Ask the page to pause and save progress, waiting until it answers or
+// SUSPEND_FLUSH_TIMEOUT passes
+#[cfg(target_os = "linux")]
+async fn prepare_for_suspend(app: &AppHandle) {
+    let state = app.state::<SuspendState>();
+    state.ready.store(false, Ordering::Relaxed);
+    info!("System suspending, pausing playback");
+    let _ = app.emit("system-suspending", ());
+
+    let deadline = std::time::Instant::now() + SUSPEND_FLUSH_TIMEOUT;
+    while !state.ready.load(Ordering::Relaxed) && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    if !state.ready.load(Ordering::Relaxed) {
+        warn!("Page did not confirm it saved progress before suspend");
+    }
+}
+
+// The page has paused playback and reported progress
+#[tauri::command]
+async fn power_suspend_ready(state: State<'_, SuspendState>) -> Result<(), String> {
+    state.ready.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod session_watch {
+    use super::{autopause, autopause_update, prepare_for_suspend};
+    use futures_util::StreamExt;
+    use tauri::{AppHandle, Emitter};
+
+    // org.freedesktop.ScreenSaver ActiveChanged(bool) on the session bus
+    pub async fn watch_screensaver(app: AppHandle) -> zbus::Result<()> {
+        let conn = zbus::Connection::session().await?;
+        let rule = zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .interface("org.freedesktop.ScreenSaver")?
+            .member("ActiveChanged")?
+            .build();
+        let mut stream = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+        while let Some(msg) = stream.next().await {
+            let Ok(msg) = msg else {
+                continue;
+            };
+            if let Ok(active) = msg.body().deserialize::<bool>() {
+                log::debug!("Screensaver active: {}", active);
+                autopause_update(&app, autopause::Trigger::ScreenLocked, active);
+            }
+        }
+        Ok(())
+    }
+
+    // logind Lock/Unlock for this process's session on the system bus
+    pub async fn watch_logind_lock(app: AppHandle) -> zbus::Result<()> {
+        let conn = zbus::Connection::system().await?;
+        let reply = conn
+            .call_method(
+                Some("org.freedesktop.login1"),
+                "/org/freedesktop/login1",
+                Some("org.freedesktop.login1.Manager"),
+                "GetSessionByPID",
+                &(std::process::id(),),
+            )
+            .await?;
+        let session: zbus::zvariant::OwnedObjectPath = reply.body().deserialize()?;
+        log::debug!("Watching logind session {}", session.as_str());
+
+        let rule = zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .sender("org.freedesktop.login1")?
+            .interface("org.freedesktop.login1.Session")?
+            .path(session.as_str())?
+            .build()
+            .to_owned();
+        let mut stream = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+        while let Some(msg) = stream.next().await {
+            let Ok(msg) = msg else {
+                continue;
+            };
+            let locked = match msg.header().member().map(|m| m.as_str()) {
+                Some("Lock") => true,
+                Some("Unlock") => false,
+                _ => continue,
+            };
+            log::debug!("logind session {}", if locked { "locked" } else { "unlocked" });
+            autopause_update(&app, autopause::Trigger::ScreenLocked, locked);
+        }
+        Ok(())
+    }
+
+    // Delays suspend until the returned descriptor is closed
+    async fn take_sleep_delay_lock(conn: &zbus::Connection) -> zbus::Result<zbus::zvariant::OwnedFd> {
+        let reply = conn
+            .call_method(
+                Some("org.freedesktop.login1"),
+                "/org/freedesktop/login1",
+                Some("org.freedesktop.login1.Manager"),
+                "Inhibit",
+                &("sleep", "Jellyfin Desktop", "Saving playback progress", "delay"),
+            )
+            .await?;
+        reply.body().deserialize()
+    }
+
+    // logind PrepareForSleep(bool) on the system bus
+    pub async fn watch_prepare_for_sleep(app: AppHandle) -> zbus::Result<()> {
+        let conn = zbus::Connection::system().await?;
+        let rule = zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .sender("org.freedesktop.login1")?
+            .interface("org.freedesktop.login1.Manager")?
+            .member("PrepareForSleep")?
+            .path("/org/freedesktop/login1")?
+            .build();
+        let mut stream = zbus::MessageStream::for_match_rule(rule, &conn, None).await?;
+
+        let mut lock = take_sleep_delay_lock(&conn)
+            .await
+            .inspect_err(|e| log::warn!("Failed to take sleep delay inhibitor: {}", e))
+            .ok();
+        while let Some(msg) = stream.next().await {
+            let Ok(msg) = msg else {
+                continue;
+            };
+            let Ok(suspending) = msg.body().deserialize::<bool>() else {
+                continue;
+            };
+            if suspending {
+                prepare_for_suspend(&app).await;
+                // Closing the descriptor lets the suspend go ahead
+                lock.take();
+            } else {
+                log::info!("System resumed from suspend");
+                lock = take_sleep_delay_lock(&conn)
+                    .await
+                    .inspect_err(|e| log::warn!("Failed to take sleep delay inhibitor: {}", e))
+                    .ok();
+                let _ = app.emit("system-resumed", ());
+            }
+        }
+        Ok(())
+    }
+}
+
 // ========================================================================
 // Windows Taskbar Integration (Progress Bar)
 // ========================================================================
@@ -691,6 +3135,10 @@ fn taskbar_set_state(_state: String) {}
 // ========================================================================
 // OS Media Controls (SMTC / MPRIS)
 // ========================================================================
+//
+// souvlaki drives SMTC on Windows and Now Playing on macOS. On Linux the
+// native MPRIS server in mpris.rs takes its place, as souvlaki cannot
+// express Rate, Shuffle, LoopStatus, Volume or CanGoNext/CanGoPrevious.
 
 struct MediaControlsState {
     controls: Mutex<Option<souvlaki::MediaControls>>,
@@ -699,16 +3147,95 @@ struct MediaControlsState {
     cached_title: Mutex<String>,
     cached_artist: Mutex<Option<String>>,
     cached_album: Mutex<Option<String>>,
+    // What the controls show: a cached file, or nothing until it downloads
     cached_cover_url: Mutex<Option<String>>,
     cached_duration_ms: Mutex<Option<u64>>,
+    // Server URL of the current item's cover
+    cover_source: Mutex<Option<String>>,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+struct MprisState {
+    server: Mutex<Option<mpris::MprisServer>>,
+    // PlaylistItemId of the current queue entry, from media_notify_tracklist
+    current_track: Mutex<Option<String>>,
+    // Numbers the mpris:trackid of items played outside a queue
+    next_track: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(target_os = "linux")]
+async fn start_mpris(app: AppHandle) {
+    let handler_app = app.clone();
+    let handler: mpris::RequestHandler = Arc::new(move |request| handle_mpris_request(&handler_app, request));
+    match mpris::MprisServer::start(handler).await {
+        Ok(server) => {
+            info!("MPRIS server registered as {}", mpris::BUS_NAME);
+            *app.state::<MprisState>().server.lock().unwrap() = Some(server);
+        }
+        Err(e) => warn!("Failed to start MPRIS server: {}", e),
+    }
+}
+
+// Mirror a change reported by the webview, once the server is up
+#[cfg(target_os = "linux")]
+fn mpris_update(app: &AppHandle, f: impl FnOnce(&mut mpris::PlayerState)) {
+    if let Some(server) = app.state::<MprisState>().server.lock().unwrap().as_ref() {
+        server.update(f);
+    }
+}
+
+// Carry out an MPRIS client request through the same events as the other
+// media controls
+#[cfg(target_os = "linux")]
+fn handle_mpris_request(app: &AppHandle, request: mpris::Request) {
+    use mpris::Request;
+    debug!("MPRIS request: {:?}", request);
+    let _ = match request {
+        Request::Action(action) => app.emit("media-control-event", action),
+        Request::SeekBy(ms) => app.emit("media-seek-by", ms),
+        Request::SetPosition(ms) => app.emit("media-set-position", ms),
+        Request::SetVolume(volume) => app.emit("media-set-volume", volume),
+        Request::SetRate(rate) => app.emit("media-set-rate", rate),
+        Request::SetShuffle(enabled) => app.emit("media-set-shuffle", enabled),
+        Request::SetLoopStatus(status) => app.emit("media-set-repeat", status.repeat_mode()),
+        Request::GoTo(track_id) => app.emit("media-goto", track_id),
+        Request::Raise => {
+            if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
+                let _ = w.unminimize();
+                let _ = w.set_focus();
+            }
+            Ok(())
+        }
+        Request::Quit => {
+            if kiosk_active(app) {
+                warn!("Kiosk mode: ignoring MPRIS quit");
+            } else {
+                app.exit(0);
+            }
+            Ok(())
+        }
+    };
 }
 
 #[tauri::command]
 fn media_notify_playback_state(
+    app: AppHandle,
     state: State<'_, MediaControlsState>,
     playing: bool,
 ) {
     state.is_playing.store(playing, Ordering::Relaxed);
+    app.state::<AutoPauseState>().engine.lock().unwrap().set_playing(playing);
+    update_tray(&app);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| {
+        s.status = if playing {
+            mpris::PlaybackStatus::Playing
+        } else {
+            mpris::PlaybackStatus::Paused
+        };
+    });
     if let Ok(mut guard) = state.controls.lock() {
         if let Some(controls) = guard.as_mut() {
             let playback = if playing {
@@ -723,52 +3250,80 @@ fn media_notify_playback_state(
 
 #[tauri::command]
 fn media_notify_metadata(
-    state: State<'_, MediaControlsState>,
+    app: AppHandle,
     title: String,
     artist: Option<String>,
     album: Option<String>,
     cover_url: Option<String>,
     duration_ms: Option<u64>,
+    media_type: Option<String>,
 ) {
     debug!(
-        "media_notify_metadata: title={}, artist={:?}, album={:?}",
-        title, artist, album
+        "media_notify_metadata: title={}, artist={:?}, album={:?}, type={:?}",
+        title, artist, album, media_type
     );
+    let media = media_type.as_deref().map_or(autopause::MediaKind::Video, autopause::MediaKind::from_media_type);
+    app.state::<AutoPauseState>().engine.lock().unwrap().set_media(Some(media));
+    let state = app.state::<MediaControlsState>();
     // Cache metadata so we can amend individual fields later
     *state.cached_title.lock().unwrap() = title.clone();
     *state.cached_artist.lock().unwrap() = artist.clone();
     *state.cached_album.lock().unwrap() = album.clone();
-    *state.cached_cover_url.lock().unwrap() = cover_url.clone();
     *state.cached_duration_ms.lock().unwrap() = duration_ms;
-
-    if let Ok(mut guard) = state.controls.lock() {
-        if let Some(controls) = guard.as_mut() {
-            controls
-                .set_metadata(souvlaki::MediaMetadata {
-                    title: Some(&title),
-                    artist: artist.as_deref(),
-                    album: album.as_deref(),
-                    cover_url: cover_url.as_deref(),
-                    duration: duration_ms.map(|ms| std::time::Duration::from_millis(ms)),
-                })
-                .ok();
-        }
+    *state.cover_source.lock().unwrap() = cover_url.clone();
+    let art_url = cover_url.as_deref().and_then(|url| cached_cover_art(&app, url));
+    if let (None, Some(url)) = (&art_url, &cover_url) {
+        fetch_current_cover_art(&app, url.clone());
     }
+    *state.cached_cover_url.lock().unwrap() = art_url.clone();
+    #[cfg(target_os = "linux")]
+    {
+        let mpris_state = app.state::<MprisState>();
+        let track_id = mpris_state.current_track.lock().unwrap().clone().unwrap_or_else(|| {
+            mpris_state.next_track.fetch_add(1, Ordering::Relaxed).to_string()
+        });
+        mpris_update(&app, |s| {
+            s.position_ms = 0;
+            s.metadata = Some(mpris::Metadata {
+                track_id,
+                title: title.clone(),
+                artist: artist.clone().filter(|a| !a.is_empty()),
+                album: album.clone().filter(|a| !a.is_empty()),
+                art_url,
+                length_ms: duration_ms,
+            });
+        });
+    }
+    apply_media_metadata(&state);
+    update_tray(&app);
 }
 
 #[tauri::command]
 fn media_notify_duration(
+    app: AppHandle,
     state: State<'_, MediaControlsState>,
     duration_ms: u64,
 ) {
     debug!("media_notify_duration: {}ms", duration_ms);
     *state.cached_duration_ms.lock().unwrap() = Some(duration_ms);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| {
+        if let Some(metadata) = s.metadata.as_mut() {
+            metadata.length_ms = Some(duration_ms);
+        }
+    });
 
     // Re-apply metadata with updated duration
+    apply_media_metadata(&state);
+}
+
+// Push the cached metadata fields to souvlaki
+fn apply_media_metadata(state: &MediaControlsState) {
     let title = state.cached_title.lock().unwrap().clone();
     let artist = state.cached_artist.lock().unwrap().clone();
     let album = state.cached_album.lock().unwrap().clone();
     let cover_url = state.cached_cover_url.lock().unwrap().clone();
+    let duration_ms = *state.cached_duration_ms.lock().unwrap();
 
     if let Ok(mut guard) = state.controls.lock() {
         if let Some(controls) = guard.as_mut() {
@@ -778,62 +3333,124 @@ fn media_notify_duration(
                     artist: artist.as_deref(),
                     album: album.as_deref(),
                     cover_url: cover_url.as_deref(),
-                    duration: Some(std::time::Duration::from_millis(duration_ms)),
+                    duration: duration_ms.map(std::time::Duration::from_millis),
                 })
                 .ok();
         }
     }
 }
 
+// Volume, rate, shuffle, repeat and queue state only reach the native MPRIS
+// server; souvlaki 0.8 has no way to express them
+
 #[tauri::command]
-fn media_notify_volume(
-    _state: State<'_, MediaControlsState>,
-    volume: f64,
-) {
-    // souvlaki 0.8 does not expose set_volume — log for future use
-    debug!("media_notify_volume: {} (not forwarded, souvlaki lacks set_volume)", volume);
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn media_notify_volume(app: AppHandle, volume: f64) {
+    debug!("media_notify_volume: {}", volume);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| s.volume = volume.clamp(0.0, 1.0));
 }
 
 #[tauri::command]
-fn media_notify_rate(
-    _state: State<'_, MediaControlsState>,
-    rate: f64,
-) {
-    // TODO: souvlaki v0.8 doesn't support playback rate — log for now
-    debug!("media_notify_rate: {} (not forwarded — souvlaki limitation)", rate);
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn media_notify_rate(app: AppHandle, rate: f64) {
+    debug!("media_notify_rate: {}", rate);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| s.rate = rate);
 }
 
 #[tauri::command]
-fn media_notify_shuffle(
-    _state: State<'_, MediaControlsState>,
-    enabled: bool,
-) {
-    // TODO: souvlaki v0.8 doesn't support shuffle property
-    debug!("media_notify_shuffle: {} (not forwarded — souvlaki limitation)", enabled);
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn media_notify_shuffle(app: AppHandle, enabled: bool) {
+    debug!("media_notify_shuffle: {}", enabled);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| s.shuffle = enabled);
 }
 
+// `mode` is a jellyfin-web repeat mode: RepeatNone, RepeatOne or RepeatAll
 #[tauri::command]
-fn media_notify_repeat(
-    _state: State<'_, MediaControlsState>,
-    mode: String,
-) {
-    // TODO: souvlaki v0.8 doesn't support repeat/loop property
-    debug!("media_notify_repeat: {} (not forwarded — souvlaki limitation)", mode);
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn media_notify_repeat(app: AppHandle, mode: String) {
+    debug!("media_notify_repeat: {}", mode);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| s.loop_status = mpris::LoopStatus::from_repeat_mode(&mode));
 }
 
 #[tauri::command]
-fn media_notify_queue(
-    _state: State<'_, MediaControlsState>,
-    can_next: bool,
-    can_prev: bool,
-) {
-    // TODO: souvlaki v0.8 doesn't support CanGoNext/CanGoPrevious toggles
-    debug!("media_notify_queue: canNext={}, canPrev={} (not forwarded — souvlaki limitation)", can_next, can_prev);
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn media_notify_queue(app: AppHandle, can_next: bool, can_prev: bool) {
+    debug!("media_notify_queue: canNext={}, canPrev={}", can_next, can_prev);
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| {
+        s.can_go_next = can_next;
+        s.can_go_previous = can_prev;
+    });
+}
+
+// One entry of the play queue, as sent by notifyTrackList
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct QueueTrack {
+    // PlaylistItemId
+    id: String,
+    title: String,
+    artist: Option<String>,
+    album: Option<String>,
+    cover_url: Option<String>,
+    duration_ms: Option<u64>,
+}
+
+// The play queue changed; published as the MPRIS TrackList on Linux
+#[tauri::command]
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn media_notify_tracklist(app: AppHandle, tracks: Vec<QueueTrack>, current: Option<String>) {
+    debug!("media_notify_tracklist: {} tracks, current={:?}", tracks.len(), current);
+    #[cfg(target_os = "linux")]
+    {
+        let tracks = tracks
+            .into_iter()
+            .map(|t| mpris::Metadata {
+                track_id: t.id,
+                title: t.title,
+                artist: t.artist.filter(|a| !a.is_empty()),
+                album: t.album.filter(|a| !a.is_empty()),
+                // Only covers already downloaded; the queue isn't prefetched
+                art_url: t.cover_url.and_then(|url| cached_cover_art(&app, &url)),
+                length_ms: t.duration_ms,
+            })
+            .collect();
+        *app.state::<MprisState>().current_track.lock().unwrap() = current.clone();
+        // Metadata may have arrived before the queue; adopt the queue's id
+        if let Some(current) = current {
+            mpris_update(&app, |s| {
+                if let Some(metadata) = s.metadata.as_mut() {
+                    metadata.track_id = current;
+                }
+            });
+        }
+        if let Some(server) = app.state::<MprisState>().server.lock().unwrap().as_ref() {
+            server.set_tracks(tracks);
+        }
+    }
 }
 
 #[tauri::command]
-fn media_notify_stop(state: State<'_, MediaControlsState>) {
+fn media_notify_stop(app: AppHandle, state: State<'_, MediaControlsState>) {
     state.is_playing.store(false, Ordering::Relaxed);
+    app.state::<AutoPauseState>().engine.lock().unwrap().set_media(None);
+    // Nothing is playing any more; the tray stops showing the old title
+    state.cached_title.lock().unwrap().clear();
+    state.cover_source.lock().unwrap().take();
+    update_tray(&app);
+    #[cfg(target_os = "linux")]
+    app.state::<MprisState>().current_track.lock().unwrap().take();
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| {
+        s.status = mpris::PlaybackStatus::Stopped;
+        s.metadata = None;
+        s.position_ms = 0;
+    });
     if let Ok(mut guard) = state.controls.lock() {
         if let Some(controls) = guard.as_mut() {
             controls
@@ -845,9 +3462,31 @@ fn media_notify_stop(state: State<'_, MediaControlsState>) {
 
 #[tauri::command]
 fn media_notify_position(
+    app: AppHandle,
     state: State<'_, MediaControlsState>,
     position_ms: u64,
 ) {
+    #[cfg(target_os = "linux")]
+    mpris_update(&app, |s| s.position_ms = position_ms);
+    update_media_position(&state, position_ms);
+}
+
+// The position jumped; MPRIS clients get a Seeked signal
+#[tauri::command]
+fn media_notify_seek(
+    app: AppHandle,
+    state: State<'_, MediaControlsState>,
+    position_ms: u64,
+) {
+    debug!("media_notify_seek: {}ms", position_ms);
+    #[cfg(target_os = "linux")]
+    if let Some(server) = app.state::<MprisState>().server.lock().unwrap().as_ref() {
+        server.seeked(position_ms);
+    }
+    update_media_position(&state, position_ms);
+}
+
+fn update_media_position(state: &MediaControlsState, position_ms: u64) {
     if let Ok(mut guard) = state.controls.lock() {
         if let Some(controls) = guard.as_mut() {
             let progress = Some(souvlaki::MediaPosition(
@@ -864,6 +3503,417 @@ fn media_notify_position(
     }
 }
 
+// ========================================================================
+// Cover Art Cache
+// ========================================================================
+//
+// OS media controls fetch artwork on their own, without the session's
+// access token. Covers are downloaded here instead, into the LRU cache in
+// artcache.rs, and the controls are given the cached file. Until a cover
+// arrives the controls show none; a server whose certificate does not
+// verify gets no covers. Setting main.coverArtCacheMb to 0 turns the cache
+// off and passes the server URL through unchanged.
+
+#[derive(Default)]
+struct CoverArtState {
+    // jellyfin-web's access token, from media_set_access_token
+    token: Mutex<Option<String>>,
+    // Covers being downloaded
+    pending: Mutex<std::collections::HashSet<String>>,
+}
+
+fn cover_art_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("covers"))
+}
+
+// None while the cache is off
+fn cover_art_cache(app: &AppHandle) -> Option<artcache::ArtCache> {
+    let limit_mb = effective_setting(app, "main", "coverArtCacheMb").as_u64().unwrap_or(0);
+    if limit_mb == 0 {
+        return None;
+    }
+    let dir = cover_art_dir(app).ok()?;
+    Some(artcache::ArtCache::new(dir, limit_mb * 1024 * 1024))
+}
+
+// What to show for a cover right away: the cached file, or the server URL
+// with the cache off. None if it still has to be downloaded.
+fn cached_cover_art(app: &AppHandle, url: &str) -> Option<String> {
+    match cover_art_cache(app) {
+        Some(cache) => {
+            let path = cache.lookup(url)?;
+            tauri::Url::from_file_path(&path).ok().map(String::from)
+        }
+        None => Some(url.to_string()),
+    }
+}
+
+// Download a cover from the active server into the cache; returns its
+// file:// URL
+async fn download_cover_art(app: &AppHandle, url: &str) -> Result<String, String> {
+    let cache = cover_art_cache(app).ok_or("Cover art cache is off")?;
+    // The token only goes to the server it belongs to
+    let parsed = tauri::Url::parse(url).map_err(|e| e.to_string())?;
+    let on_server = app
+        .state::<ServerState>()
+        .active_url()
+        .is_some_and(|server| server.origin() == parsed.origin());
+    if !on_server {
+        return Err("Cover is not on the active server".to_string());
+    }
+
+    // The request carries the session token, so the certificate is always
+    // verified, unlike the connectivity check
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client.get(url);
+    let token = app.state::<CoverArtState>().token.lock().unwrap().clone();
+    if let Some(token) = token {
+        request = request.header(
+            reqwest::header::AUTHORIZATION,
+            format!("MediaBrowser Token=\"{}\"", token),
+        );
+    }
+    let mut resp = request.send().await.map_err(|e| e.to_string())?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(format!("Server returned status {}", status));
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let extension = artcache::extension_for(&content_type)
+        .ok_or_else(|| format!("Not an image: {:?}", content_type))?;
+
+    // Refuse oversized covers up front, and stop reading once a body without
+    // (or lying about) its length goes past the limit
+    let max_bytes = cache.max_image_bytes();
+    if let Some(length) = resp.content_length().filter(|&length| length > max_bytes) {
+        return Err(format!("Cover is {} bytes, more than the {} allowed", length, max_bytes));
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        if (bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(format!("Cover is more than the {} bytes allowed", max_bytes));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let path = cache.store(url, extension, &bytes).map_err(|e| e.to_string())?;
+    debug!("Cached cover art {} as {}", url, path.display());
+    tauri::Url::from_file_path(&path)
+        .map(String::from)
+        .map_err(|_| format!("Invalid cache path {}", path.display()))
+}
+
+// Download the current item's cover and show it once it arrives
+fn fetch_current_cover_art(app: &AppHandle, url: String) {
+    if !app.state::<CoverArtState>().pending.lock().unwrap().insert(url.clone()) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = download_cover_art(&app, &url).await;
+        app.state::<CoverArtState>().pending.lock().unwrap().remove(&url);
+        match result {
+            Ok(art_url) => show_cover_art(&app, &url, art_url),
+            Err(e) => {
+                // Clients that can reach the server may still load it themselves
+                warn!("Failed to fetch cover art {}, passing the URL on: {}", url, e);
+                show_cover_art(&app, &url, url.clone());
+            }
+        }
+    });
+}
+
+fn show_cover_art(app: &AppHandle, source: &str, art_url: String) {
+    let state = app.state::<MediaControlsState>();
+    // Playback moved on while the cover downloaded
+    if state.cover_source.lock().unwrap().as_deref() != Some(source) {
+        return;
+    }
+    *state.cached_cover_url.lock().unwrap() = Some(art_url.clone());
+    #[cfg(target_os = "linux")]
+    mpris_update(app, |s| {
+        if let Some(metadata) = s.metadata.as_mut() {
+            metadata.art_url = Some(art_url);
+        }
+    });
+    apply_media_metadata(&state);
+}
+
+// Token for cover downloads; the webview passes it as sessions change
+#[tauri::command]
+fn media_set_access_token(state: State<'_, CoverArtState>, token: Option<String>) {
+    *state.token.lock().unwrap() = token.filter(|t| !t.is_empty());
+}
+
+// Delete every cached cover; returns the number of bytes freed
+#[tauri::command]
+fn media_clear_cover_cache(app: AppHandle) -> Result<u64, String> {
+    let dir = cover_art_dir(&app)?;
+    let freed = artcache::ArtCache::new(dir, 0)
+        .clear()
+        .map_err(|e| format!("Failed to clear cover art cache: {}", e))?;
+    info!("Cleared cover art cache ({} bytes)", freed);
+    // The current cover was among them
+    let source = app.state::<MediaControlsState>().cover_source.lock().unwrap().clone();
+    if let Some(url) = source {
+        fetch_current_cover_art(&app, url);
+    }
+    Ok(freed)
+}
+
+// ========================================================================
+// System Tray
+// ========================================================================
+//
+// Tray menu with the current title, transport controls, show/hide and quit.
+// Transport items emit the same `media-control-event` actions as the OS
+// media controls, so the webview handles both alike. With main.closeToTray
+// on, closing the window hides it to the tray instead of quitting.
+
+struct TrayState {
+    title: tauri::menu::MenuItem<tauri::Wry>,
+    play_pause: tauri::menu::MenuItem<tauri::Wry>,
+    show_hide: tauri::menu::MenuItem<tauri::Wry>,
+}
+
+fn create_tray(app: &AppHandle) -> tauri::Result<TrayState> {
+    use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+    use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+
+    // Menu ids double as the media-control-event actions
+    let title = MenuItem::with_id(app, "title", "Nothing playing", false, None::<&str>)?;
+    let play_pause = MenuItem::with_id(app, "play_pause", "Play", true, None::<&str>)?;
+    let previous = MenuItem::with_id(app, "previous_track", "Previous", true, None::<&str>)?;
+    let next = MenuItem::with_id(app, "next_track", "Next", true, None::<&str>)?;
+    let stop = MenuItem::with_id(app, "stop", "Stop", true, None::<&str>)?;
+    let show_hide = MenuItem::with_id(app, "show_hide", "Hide Window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &title,
+            &PredefinedMenuItem::separator(app)?,
+            &play_pause,
+            &previous,
+            &next,
+            &stop,
+            &PredefinedMenuItem::separator(app)?,
+            &show_hide,
+            &quit,
+        ],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip("Jellyfin Desktop")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_tray_menu(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            // Not delivered on Linux, where every click opens the menu
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                toggle_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    Ok(TrayState {
+        title,
+        play_pause,
+        show_hide,
+    })
+}
+
+fn handle_tray_menu(app: &AppHandle, id: &str) {
+    debug!("Tray menu: {}", id);
+    match id {
+        "play_pause" | "previous_track" | "next_track" | "stop" => {
+            let _ = app.emit("media-control-event", id);
+        }
+        "show_hide" => toggle_main_window(app),
+        "quit" => {
+            if kiosk_active(app) {
+                warn!("Kiosk mode: ignoring tray quit");
+                return;
+            }
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+fn toggle_main_window(app: &AppHandle) {
+    let Some(win) = app.get_webview_window("main") else {
+        return;
+    };
+    let shown = win.is_visible().unwrap_or(true) && !win.is_minimized().unwrap_or(false);
+    if shown {
+        if kiosk_active(app) {
+            warn!("Kiosk mode: ignoring tray hide");
+            return;
+        }
+        let _ = win.hide();
+    } else {
+        let _ = win.show();
+        let _ = win.unminimize();
+        let _ = win.set_focus();
+    }
+    update_tray(app);
+}
+
+// Refresh the title, play/pause and show/hide labels
+fn update_tray(app: &AppHandle) {
+    let Some(tray) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let media = app.try_state::<MediaControlsState>();
+    let playing = media
+        .as_ref()
+        .is_some_and(|m| m.is_playing.load(Ordering::Relaxed));
+    let title = media
+        .as_ref()
+        .map(|m| {
+            let title = m.cached_title.lock().unwrap().clone();
+            match m.cached_artist.lock().unwrap().as_deref() {
+                Some(artist) if !title.is_empty() && !artist.is_empty() => format!("{} — {}", title, artist),
+                _ => title,
+            }
+        })
+        .filter(|title| !title.is_empty());
+    let visible = app
+        .get_webview_window("main")
+        .is_some_and(|w| w.is_visible().unwrap_or(true) && !w.is_minimized().unwrap_or(false));
+
+    let _ = tray.title.set_text(title.as_deref().unwrap_or("Nothing playing"));
+    let _ = tray.play_pause.set_text(if playing { "Pause" } else { "Play" });
+    let _ = tray.show_hide.set_text(if visible { "Hide Window" } else { "Show Window" });
+}
+
+// Close hides the window when main.closeToTray is on and a tray icon exists
+// to bring it back
+fn close_to_tray(app: &AppHandle) -> bool {
+    if app.try_state::<TrayState>().is_none() || kiosk_active(app) {
+        return false;
+    }
+    effective_setting(app, "main", "closeToTray").as_bool().unwrap_or(false)
+}
+
+// ========================================================================
+// Global Hotkeys
+// ========================================================================
+//
+// With hotkeys.enabled on, the bindings in the hotkeys section are grabbed
+// system-wide so media keys keep working while another app has focus. Each
+// binding key names the `media-control-event` action it emits. A binding is
+// reported as a conflict when an earlier action already uses the same
+// combination or the OS refuses the grab (another app or the desktop owns
+// it). Wayland has no global grabs, so there every binding fails.
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HotkeyBinding {
+    action: String,
+    shortcut: String,
+    registered: bool,
+    // Why the binding is not active
+    conflict: Option<String>,
+}
+
+#[derive(Default)]
+struct HotkeyState {
+    bindings: Mutex<Vec<HotkeyBinding>>,
+}
+
+// Drop every grab and register the current bindings again. Emits
+// `hotkeys-changed` with the resulting status.
+fn register_hotkeys(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        warn!("Failed to release global hotkeys: {}", e);
+    }
+
+    let mut bindings = Vec::new();
+    if effective_setting(app, "hotkeys", "enabled").as_bool().unwrap_or(false) {
+        let descriptors = settings::section("hotkeys").map(|s| s.settings).unwrap_or(&[]);
+        // Shortcut id -> action that owns it
+        let mut taken: HashMap<u32, &str> = HashMap::new();
+        for desc in descriptors
+            .iter()
+            .filter(|d| matches!(d.kind, settings::SettingKind::Shortcut))
+        {
+            let value = effective_setting(app, "hotkeys", desc.key);
+            let text = value.as_str().unwrap_or_default().trim();
+            if text.is_empty() {
+                continue;
+            }
+            let mut binding = HotkeyBinding {
+                action: desc.key.to_string(),
+                shortcut: text.to_string(),
+                registered: false,
+                conflict: None,
+            };
+            match text.parse::<Shortcut>() {
+                Err(e) => binding.conflict = Some(format!("Invalid key combination: {}", e)),
+                Ok(shortcut) => match taken.get(&shortcut.id()) {
+                    Some(other) => binding.conflict = Some(format!("Already bound to {}", other)),
+                    None => {
+                        let action = desc.key;
+                        let result = shortcuts.on_shortcut(shortcut, move |app, _, event| {
+                            if event.state == ShortcutState::Pressed {
+                                debug!("Global hotkey: {}", action);
+                                let _ = app.emit("media-control-event", action);
+                            }
+                        });
+                        match result {
+                            Ok(()) => {
+                                taken.insert(shortcut.id(), action);
+                                binding.registered = true;
+                            }
+                            Err(e) => binding.conflict = Some(format!("Unavailable: {}", e)),
+                        }
+                    }
+                },
+            }
+            if let Some(conflict) = &binding.conflict {
+                warn!("Global hotkey {} for {}: {}", binding.shortcut, binding.action, conflict);
+            }
+            bindings.push(binding);
+        }
+        info!(
+            "Global hotkeys: {} of {} bindings registered",
+            bindings.iter().filter(|b| b.registered).count(),
+            bindings.len()
+        );
+    }
+
+    *app.state::<HotkeyState>().bindings.lock().unwrap() = bindings.clone();
+    let _ = app.emit("hotkeys-changed", &bindings);
+}
+
+// Bindings with their registration state and conflicts
+#[tauri::command]
+async fn hotkeys_get_status(state: State<'_, HotkeyState>) -> Result<Vec<HotkeyBinding>, String> {
+    Ok(state.bindings.lock().unwrap().clone())
+}
+
 // ========================================================================
 // Webview Log Forwarding
 // ========================================================================
@@ -888,8 +3938,10 @@ async fn log_from_webview(level: String, message: String, context: Option<String
 
 #[derive(Debug, Clone)]
 struct CliArgs {
-    fullscreen: bool,
-    tv_mode: bool,
+    // One-off overrides of the layout's saved state; None keeps it
+    fullscreen: Option<bool>,
+    layout: Option<LayoutMode>,
+    kiosk: bool,
     log_level: Option<String>,
 }
 
@@ -902,27 +3954,35 @@ fn parse_cli_args() -> CliArgs {
         .arg(Arg::new("fullscreen").long("fullscreen").action(clap::ArgAction::SetTrue).help("Start in fullscreen mode"))
         .arg(Arg::new("windowed").long("windowed").action(clap::ArgAction::SetTrue).help("Start in windowed mode"))
         .arg(Arg::new("tv").long("tv").action(clap::ArgAction::SetTrue).help("Start in TV layout mode"))
-        .arg(Arg::new("desktop").long("desktop").action(clap::ArgAction::SetTrue).help("Start in desktop layout mode (default)"))
+        .arg(Arg::new("desktop").long("desktop").action(clap::ArgAction::SetTrue).help("Start in desktop layout mode"))
+        .arg(Arg::new("kiosk").long("kiosk").action(clap::ArgAction::SetTrue).help("Start in kiosk mode (locked fullscreen, PIN-protected exit)"))
         .arg(Arg::new("log-level").long("log-level").value_name("LEVEL").help("Log level: debug, info, warn, error"))
         .get_matches();
 
     let fullscreen = if matches.get_flag("windowed") {
-        false
+        Some(false)
+    } else if matches.get_flag("fullscreen") {
+        Some(true)
     } else {
-        matches.get_flag("fullscreen")
+        None
     };
 
-    let tv_mode = if matches.get_flag("desktop") {
-        false
+    let layout = if matches.get_flag("desktop") {
+        Some(LayoutMode::Desktop)
+    } else if matches.get_flag("tv") {
+        Some(LayoutMode::Tv)
     } else {
-        matches.get_flag("tv")
+        None
     };
 
+    let kiosk = matches.get_flag("kiosk");
+
     let log_level = matches.get_one::<String>("log-level").cloned();
 
     CliArgs {
         fullscreen,
-        tv_mode,
+        layout,
+        kiosk,
         log_level,
     }
 }
@@ -950,6 +4010,7 @@ pub fn run() {
         // Single-instance: focus existing window when second instance is launched
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             if let Some(w) = app.get_webview_window("main") {
+                let _ = w.show();
                 let _ = w.unminimize();
                 let _ = w.set_focus();
             }
@@ -972,19 +4033,37 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(move |app| {
             info!("Jellyfin Desktop starting up");
             let cli = &cli_args_clone;
 
-            if cli.fullscreen {
-                info!("CLI: --fullscreen requested");
+            if let Some(fullscreen) = cli.fullscreen {
+                info!("CLI: --{} requested", if fullscreen { "fullscreen" } else { "windowed" });
             }
-            if cli.tv_mode {
-                info!("CLI: --tv mode requested");
+            if let Some(layout) = cli.layout {
+                info!("CLI: --{} layout requested", layout.as_str());
+            }
+            if cli.kiosk {
+                info!("CLI: --kiosk mode requested");
             }
 
             // Manage cancellation flag for server connectivity checks
             app.manage(ConnectivityCancelFlag(Arc::new(AtomicBool::new(false))));
+            app.manage(ServerState::default());
+            // Admin policy (defaults and locked settings) from the system location
+            app.manage(policy::Policy::load());
+            app.manage(IpcAudit::default());
+            app.manage(AutoPauseState::default());
+            app.manage(SuspendState::default());
+            app.manage(KioskState {
+                forced: cli.kiosk,
+                ..Default::default()
+            });
+            if cli.kiosk && app.state::<policy::Policy>().kiosk_pin().is_none() {
+                warn!("--kiosk without a kioskPin in the admin policy: exit and restart are disabled for this run");
+            }
 
             // Log the app data directory for easy log file discovery
             if let Ok(log_dir) = app.path().app_log_dir() {
@@ -994,12 +4073,16 @@ pub fn run() {
                 info!("Data directory: {}", data_dir.display());
             }
 
-            // If CLI requests TV mode, inject a script to override jmpInfo.mode
-            let mode_script = if cli.tv_mode {
-                "\n(function(){ if(window.jmpInfo) window.jmpInfo.mode = 'tv'; })();\n"
-            } else {
-                ""
-            };
+            // ── Migrate settings.json to the current schema version ──
+            if let Err(e) = migrate_settings_store(app.handle()) {
+                error!("Settings migration failed: {}", e);
+            }
+            spawn_settings_watcher(app.handle().clone());
+
+            // The page reads the active layout through window_get_layout_mode
+            app.manage(LayoutState {
+                mode: Mutex::new(startup_layout(app.handle(), cli.layout)),
+            });
 
             // Create main window from config, adding our initialization scripts
             // The window has "create": false in tauri.conf.json so Tauri doesn't auto-create it
@@ -1011,9 +4094,9 @@ pub fn run() {
                 .initialization_script(MPV_AUDIO_PLAYER)
                 .initialization_script(INPUT_PLUGIN);
 
-            if !mode_script.is_empty() {
-                builder = builder.initialization_script(mode_script);
-            }
+            // Keep the webview on the connect page and the active server
+            let nav_app = app.handle().clone();
+            builder = builder.on_navigation(move |url| navigation_allowed(&nav_app, url));
 
             let win = builder.build()?;
             info!("Main window created successfully");
@@ -1033,8 +4116,18 @@ pub fn run() {
                 }
             }
 
-            // ── Initialize OS media controls (SMTC on Windows, MPRIS on Linux) ──
-            {
+            // ── Initialize OS media controls (SMTC on Windows, native MPRIS server on Linux) ──
+            #[cfg(target_os = "linux")]
+            let controls: Option<souvlaki::MediaControls> = {
+                app.manage(MprisState::default());
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    start_mpris(app_handle).await;
+                });
+                None
+            };
+            #[cfg(not(target_os = "linux"))]
+            let controls = {
                 #[cfg(target_os = "windows")]
                 let hwnd = {
                     use raw_window_handle::{HasWindowHandle, RawWindowHandle};
@@ -1095,12 +4188,17 @@ pub fn run() {
                                 }
                                 MediaControlEvent::Raise => {
                                     if let Some(w) = app_handle.get_webview_window("main") {
+                                        let _ = w.show();
                                         let _ = w.unminimize();
                                         let _ = w.set_focus();
                                     }
                                     return;
                                 }
                                 MediaControlEvent::Quit => {
+                                    if kiosk_active(&app_handle) {
+                                        warn!("Kiosk mode: ignoring media controls quit");
+                                        return;
+                                    }
                                     app_handle.exit(0);
                                     return;
                                 }
@@ -1110,61 +4208,36 @@ pub fn run() {
                         }) {
                             warn!("Failed to attach media controls handler: {:?}", e);
                         }
-                        app.manage(MediaControlsState {
-                            controls: Mutex::new(Some(controls)),
-                            is_playing: AtomicBool::new(false),
-                            cached_title: Mutex::new(String::new()),
-                            cached_artist: Mutex::new(None),
-                            cached_album: Mutex::new(None),
-                            cached_cover_url: Mutex::new(None),
-                            cached_duration_ms: Mutex::new(None),
-                        });
-                        info!("OS media controls initialized (SMTC/MPRIS)");
+                        info!("OS media controls initialized");
+                        Some(controls)
                     }
                     Err(e) => {
                         warn!("Failed to initialize OS media controls: {:?}", e);
-                        app.manage(MediaControlsState {
-                            controls: Mutex::new(None),
-                            is_playing: AtomicBool::new(false),
-                            cached_title: Mutex::new(String::new()),
-                            cached_artist: Mutex::new(None),
-                            cached_album: Mutex::new(None),
-                            cached_cover_url: Mutex::new(None),
-                            cached_duration_ms: Mutex::new(None),
-                        });
-                    }
-                }
-            }
-
-            // ── Apply CLI fullscreen override ──
-            if cli.fullscreen {
-                let _ = win.set_fullscreen(true);
-            }
-
-            // ── Restore saved window geometry (only if not overridden by CLI) ──
-            if !cli.fullscreen {
-                let store = app.store("settings.json").ok();
-                if let Some(ref store) = store {
-                    let x = store.get("state.geometry.x").and_then(|v| v.as_i64()).map(|v| v as i32);
-                    let y = store.get("state.geometry.y").and_then(|v| v.as_i64()).map(|v| v as i32);
-                    let w = store.get("state.geometry.w").and_then(|v| v.as_u64()).map(|v| v as u32);
-                    let h = store.get("state.geometry.h").and_then(|v| v.as_u64()).map(|v| v as u32);
-                    let maximized = store.get("state.geometry.maximized").and_then(|v| v.as_bool()).unwrap_or(false);
-
-                    if let (Some(x), Some(y), Some(w), Some(h)) = (x, y, w, h) {
-                        // Sanity check: only restore if size is reasonable
-                        if w >= 200 && h >= 150 {
-                            info!("Restoring window geometry: {}x{} at ({}, {}), maximized={}", w, h, x, y, maximized);
-                            let _ = win.set_position(tauri::PhysicalPosition::new(x, y));
-                            let _ = win.set_size(tauri::PhysicalSize::new(w, h));
-                        }
-                    }
-                    if maximized {
-                        info!("Restoring maximized state");
-                        let _ = win.maximize();
+                        None
                     }
                 }
-            }
+            };
+            app.manage(MediaControlsState {
+                controls: Mutex::new(controls),
+                is_playing: AtomicBool::new(false),
+                cached_title: Mutex::new(String::new()),
+                cached_artist: Mutex::new(None),
+                cached_album: Mutex::new(None),
+                cached_cover_url: Mutex::new(None),
+                cached_duration_ms: Mutex::new(None),
+                cover_source: Mutex::new(None),
+            });
+            app.manage(CoverArtState::default());
+
+            // ── Apply the layout's window state (CLI overrides fullscreen, kiosk forces it) ──
+            app.manage(FullscreenState::default());
+            app.manage(PipState::default());
+            app.manage(VideoFitState::default());
+            app.manage(DisplayState {
+                switcher: Mutex::new(display::detect_backend().map(display::DisplayModeSwitcher::new)),
+            });
+            let layout = app.state::<LayoutState>().current();
+            apply_layout_window_state(app.handle(), &win, layout, cli.fullscreen);
 
             // ── Debounced geometry save on move/resize ──
             let debounce_timer: Arc<Mutex<Option<std::time::Instant>>> =
@@ -1199,6 +4272,166 @@ pub fn run() {
                 }
             });
 
+            // ── System tray ──
+            match create_tray(app.handle()) {
+                Ok(tray) => {
+                    app.manage(tray);
+                    info!("System tray icon created");
+                }
+                Err(e) => warn!("Failed to create system tray icon: {}", e),
+            }
+            let app_handle = app.handle().clone();
+            win.on_window_event(move |event| match event {
+                tauri::WindowEvent::CloseRequested { api, .. } if close_to_tray(&app_handle) => {
+                    info!("Closing to tray");
+                    api.prevent_close();
+                    if let Some(w) = app_handle.get_webview_window("main") {
+                        let _ = w.hide();
+                    }
+                    update_tray(&app_handle);
+                }
+                tauri::WindowEvent::Focused(_) | tauri::WindowEvent::Resized(_) => update_tray(&app_handle),
+                _ => {}
+            });
+
+            // ── Global hotkeys (re-registered whenever the section changes) ──
+            app.manage(HotkeyState::default());
+            register_hotkeys(app.handle());
+            let app_handle = app.handle().clone();
+            app.listen("settings-value-changed", move |event| {
+                let Ok(change) = serde_json::from_str::<Value>(event.payload()) else {
+                    return;
+                };
+                if change["section"] == "hotkeys" {
+                    register_hotkeys(&app_handle);
+                }
+            });
+
+            // ── Live keymap reload ──
+            let app_handle = app.handle().clone();
+            app.listen("settings-values-changed", move |event| {
+                let Ok(payload) = serde_json::from_str::<Value>(event.payload()) else {
+                    return;
+                };
+                let touches_input = payload["changes"]
+                    .as_array()
+                    .is_some_and(|changes| changes.iter().any(|c| c["section"] == "input"));
+                if touches_input {
+                    let keymap = current_keymap(&app_handle);
+                    for conflict in &keymap.conflicts {
+                        warn!("Key binding conflict: {} is bound to {:?}", conflict.keys, conflict.actions);
+                    }
+                    let _ = app_handle.emit("input-keymap-changed", keymap);
+                }
+            });
+
+            // ── Layout changes from settings (the modal, sync or window_set_layout_mode) ──
+            let app_handle = app.handle().clone();
+            app.listen("settings-value-changed", move |event| {
+                let Ok(change) = serde_json::from_str::<Value>(event.payload()) else {
+                    return;
+                };
+                if change["section"] != "main" || change["key"] != "layout" {
+                    return;
+                }
+                if let Some(mode) = change["value"].as_str().and_then(LayoutMode::parse) {
+                    if let Err(e) = switch_layout(&app_handle, mode) {
+                        warn!("Failed to switch layout: {}", e);
+                    }
+                }
+            });
+
+            // ── Picture-in-picture corner snapping ──
+            let app_handle = app.handle().clone();
+            win.on_window_event(move |event| {
+                if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) = event {
+                    schedule_pip_settle(&app_handle);
+                }
+            });
+
+            // ── Video aspect lock ──
+            let app_handle = app.handle().clone();
+            win.on_window_event(move |event| {
+                if let tauri::WindowEvent::Resized(size) = event {
+                    if let Some(w) = app_handle.get_webview_window("main") {
+                        lock_video_aspect(&app_handle, &w, *size);
+                    }
+                }
+            });
+
+            // ── Auto pause on minimize, focus loss and screen lock ──
+            let app_handle = app.handle().clone();
+            win.on_window_event(move |event| match event {
+                tauri::WindowEvent::Focused(focused) => autopause_window_changed(&app_handle, Some(*focused)),
+                tauri::WindowEvent::Resized(_) => autopause_window_changed(&app_handle, None),
+                _ => {}
+            });
+            #[cfg(target_os = "linux")]
+            {
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = session_watch::watch_screensaver(app_handle).await {
+                        warn!("Screensaver lock signals unavailable: {}", e);
+                    }
+                });
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = session_watch::watch_logind_lock(app_handle).await {
+                        warn!("logind lock signals unavailable: {}", e);
+                    }
+                });
+                // ── Pause and save progress before system suspend ──
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = session_watch::watch_prepare_for_sleep(app_handle).await {
+                        warn!("logind sleep signals unavailable: {}", e);
+                    }
+                });
+            }
+
+            // ── Kiosk mode enforcement ──
+            let app_handle = app.handle().clone();
+            win.on_window_event(move |event| match event {
+                tauri::WindowEvent::CloseRequested { api, .. } if kiosk_active(&app_handle) => {
+                    warn!("Kiosk mode: ignoring window close request");
+                    api.prevent_close();
+                }
+                tauri::WindowEvent::Resized(_)
+                | tauri::WindowEvent::Moved(_)
+                | tauri::WindowEvent::Focused(_)
+                    if kiosk_active(&app_handle) =>
+                {
+                    enforce_kiosk_fullscreen(app_handle.clone());
+                }
+                _ => {}
+            });
+            // A smaller cover art cache takes effect immediately
+            let app_handle = app.handle().clone();
+            app.listen("settings-value-changed", move |event| {
+                let Ok(change) = serde_json::from_str::<Value>(event.payload()) else {
+                    return;
+                };
+                if change["section"] == "main" && change["key"] == "coverArtCacheMb" {
+                    if let Some(Err(e)) = cover_art_cache(&app_handle).map(|cache| cache.evict()) {
+                        warn!("Failed to trim cover art cache: {}", e);
+                    }
+                }
+            });
+
+            // Turning forceAlwaysFS on at runtime takes effect immediately
+            let app_handle = app.handle().clone();
+            app.listen("settings-value-changed", move |event| {
+                let Ok(change) = serde_json::from_str::<Value>(event.payload()) else {
+                    return;
+                };
+                if change["section"] == "main" && change["key"] == "forceAlwaysFS" {
+                    if change["value"] == true && app_handle.state::<policy::Policy>().kiosk_pin().is_none() {
+                        warn!("main.forceAlwaysFS is on without a kioskPin in the admin policy; anyone can turn it off");
+                    }
+                    enforce_kiosk_fullscreen(app_handle.clone());
+                }
+            });
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -1212,8 +4445,11 @@ pub fn run() {
             settings_get_value,
             settings_set_value,
             settings_set_values,
+            settings_get_effective,
+            settings_clear_override,
             settings_delete_section,
             settings_get_all,
+            settings_schema,
             // Window
             window_set_title,
             window_set_fullscreen,
@@ -1223,6 +4459,21 @@ pub fn run() {
             window_raise,
             window_set_cursor_visible,
             window_save_geometry,
+            window_list_monitors,
+            window_set_picture_in_picture,
+            window_is_picture_in_picture,
+            window_get_layout_mode,
+            window_set_layout_mode,
+            window_set_cinema_mode,
+            window_is_cinema_mode,
+            window_start_drag,
+            window_toggle_maximize,
+            window_set_video_size,
+            window_fit_to_video,
+            // Display
+            display_list_outputs,
+            display_match_refresh_rate,
+            display_restore,
             // System
             system_hello,
             system_open_external_url,
@@ -1231,8 +4482,18 @@ pub fn run() {
             system_debug_info,
             system_check_for_updates,
             system_network_addresses,
+            // Kiosk
+            kiosk_status,
+            // Hotkeys
+            hotkeys_get_status,
+            // Keymap
+            input_get_keymap,
+            input_set_binding,
+            input_reset_bindings,
+            input_get_conflicts,
             // Power
             power_set_screensaver_enabled,
+            power_suspend_ready,
             // Taskbar
             taskbar_set_progress,
             taskbar_set_state,
@@ -1241,15 +4502,27 @@ pub fn run() {
             media_notify_metadata,
             media_notify_stop,
             media_notify_position,
+            media_notify_seek,
             media_notify_duration,
             media_notify_volume,
             media_notify_rate,
             media_notify_shuffle,
             media_notify_repeat,
             media_notify_queue,
+            media_notify_tracklist,
+            media_set_access_token,
+            media_clear_cover_cache,
             // Logging
             log_from_webview,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Leave the display the way we found it
+                if let Some(switcher) = app.state::<DisplayState>().switcher.lock().unwrap().as_mut() {
+                    let _ = switcher.restore();
+                }
+            }
+        });
 }
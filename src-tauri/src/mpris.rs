@@ -0,0 +1,1186 @@
+// ========================================================================
+// MPRIS
+// ========================================================================
+//
+// Native org.mpris.MediaPlayer2 server on the session bus (Linux). Unlike
+// souvlaki it exposes every Player property, including Rate, Shuffle,
+// LoopStatus, Volume and CanGoNext/CanGoPrevious, and emits
+// PropertiesChanged when they change.
+//
+// The webview stays the source of truth: PlayerState mirrors what it
+// reports, and method calls or property writes from clients become
+// Requests for the webview to carry out. Writes are not applied here; the
+// property changes once the webview reports the new value.
+//
+// The TrackList interface publishes the play queue the webview pushes with
+// set_tracks. Track ids are jellyfin-web PlaylistItemIds, so GoTo maps
+// straight back to an entry in its queue. Edits are diffed into
+// TrackAdded/TrackRemoved; anything else (a new queue, a reorder) is sent
+// as TrackListReplaced.
+//
+// The server uses the bus from DBUS_SESSION_BUS_ADDRESS, so it can be run
+// against a private `dbus-daemon --session` and driven with busctl or
+// playerctl.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::{fdo, interface};
+
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.jellyfin_desktop";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+const TRACK_PREFIX: &str = "/org/jellyfin/desktop/track";
+
+// jellyfin-web playback rates
+const MINIMUM_RATE: f64 = 0.5;
+const MAXIMUM_RATE: f64 = 2.0;
+
+// A client request for the webview to carry out
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    // A `media-control-event` action: play, pause, play_pause, stop,
+    // next_track or previous_track
+    Action(&'static str),
+    SeekBy(i64),
+    SetPosition(u64),
+    SetVolume(f64),
+    SetRate(f64),
+    SetShuffle(bool),
+    SetLoopStatus(LoopStatus),
+    // Play the queue entry with this track id
+    GoTo(String),
+    Raise,
+    Quit,
+}
+
+pub type RequestHandler = Arc<dyn Fn(Request) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    #[default]
+    Stopped,
+}
+
+impl PlaybackStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            PlaybackStatus::Playing => "Playing",
+            PlaybackStatus::Paused => "Paused",
+            PlaybackStatus::Stopped => "Stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopStatus {
+    #[default]
+    None,
+    Track,
+    Playlist,
+}
+
+impl LoopStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            LoopStatus::None => "None",
+            LoopStatus::Track => "Track",
+            LoopStatus::Playlist => "Playlist",
+        }
+    }
+
+    fn parse(value: &str) -> Option<LoopStatus> {
+        match value {
+            "None" => Some(LoopStatus::None),
+            "Track" => Some(LoopStatus::Track),
+            "Playlist" => Some(LoopStatus::Playlist),
+            _ => None,
+        }
+    }
+
+    // jellyfin-web repeat modes
+    pub fn from_repeat_mode(mode: &str) -> LoopStatus {
+        match mode {
+            "RepeatOne" => LoopStatus::Track,
+            "RepeatAll" => LoopStatus::Playlist,
+            _ => LoopStatus::None,
+        }
+    }
+
+    pub fn repeat_mode(self) -> &'static str {
+        match self {
+            LoopStatus::None => "RepeatNone",
+            LoopStatus::Track => "RepeatOne",
+            LoopStatus::Playlist => "RepeatAll",
+        }
+    }
+}
+
+// The current track, or an entry in the track list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    // Last segment of the mpris:trackid object path
+    pub track_id: String,
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub art_url: Option<String>,
+    pub length_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerState {
+    pub status: PlaybackStatus,
+    pub loop_status: LoopStatus,
+    pub rate: f64,
+    pub shuffle: bool,
+    pub volume: f64,
+    pub position_ms: u64,
+    // None while nothing is loaded
+    pub metadata: Option<Metadata>,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        PlayerState {
+            status: PlaybackStatus::Stopped,
+            loop_status: LoopStatus::None,
+            rate: 1.0,
+            shuffle: false,
+            volume: 1.0,
+            position_ms: 0,
+            metadata: None,
+            can_go_next: false,
+            can_go_previous: false,
+        }
+    }
+}
+
+impl PlayerState {
+    fn track_path(&self) -> OwnedObjectPath {
+        match &self.metadata {
+            Some(m) => track_path(&m.track_id),
+            None => no_track(),
+        }
+    }
+}
+
+type SharedState = Arc<Mutex<PlayerState>>;
+type SharedTracks = Arc<Mutex<Vec<Metadata>>>;
+
+fn no_track() -> OwnedObjectPath {
+    ObjectPath::from_static_str_unchecked(NO_TRACK).into()
+}
+
+// Object path elements only allow [A-Za-z0-9_]. Ids that differ only in
+// the replaced characters ("a-b", "a_b") get different paths through a hash
+// of the raw id.
+fn track_path(track_id: &str) -> OwnedObjectPath {
+    let element: String = track_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let mut hasher = DefaultHasher::new();
+    track_id.hash(&mut hasher);
+    let path = format!("{}/{}_{:016x}", TRACK_PREFIX, element, hasher.finish());
+    OwnedObjectPath::try_from(path).unwrap_or_else(|_| no_track())
+}
+
+fn owned(value: Value<'_>) -> OwnedValue {
+    // Only values carrying file descriptors fail to convert
+    OwnedValue::try_from(value).expect("metadata values hold no file descriptors")
+}
+
+fn metadata_map(path: OwnedObjectPath, meta: Option<&Metadata>) -> HashMap<String, OwnedValue> {
+    let mut map = HashMap::new();
+    map.insert("mpris:trackid".to_string(), owned(Value::from(path)));
+    let Some(meta) = meta else {
+        return map;
+    };
+    map.insert(
+        "xesam:title".to_string(),
+        owned(Value::from(meta.title.clone())),
+    );
+    if let Some(artist) = &meta.artist {
+        map.insert(
+            "xesam:artist".to_string(),
+            owned(Value::from(vec![artist.clone()])),
+        );
+    }
+    if let Some(album) = &meta.album {
+        map.insert("xesam:album".to_string(), owned(Value::from(album.clone())));
+    }
+    if let Some(art_url) = &meta.art_url {
+        map.insert(
+            "mpris:artUrl".to_string(),
+            owned(Value::from(art_url.clone())),
+        );
+    }
+    if let Some(length_ms) = meta.length_ms {
+        map.insert(
+            "mpris:length".to_string(),
+            owned(Value::from(length_ms as i64 * 1000)),
+        );
+    }
+    map
+}
+
+// ------------------------------------------------------------------------
+// org.mpris.MediaPlayer2
+// ------------------------------------------------------------------------
+
+struct Root {
+    handler: RequestHandler,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {
+        (self.handler)(Request::Raise);
+    }
+
+    fn quit(&self) {
+        (self.handler)(Request::Quit);
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_quit(&self) -> bool {
+        true
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_raise(&self) -> bool {
+        true
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn has_track_list(&self) -> bool {
+        true
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn identity(&self) -> &str {
+        "Jellyfin Desktop"
+    }
+
+    // Name of the bundled .desktop file
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn desktop_entry(&self) -> &str {
+        "jellyfin-tauri"
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// ------------------------------------------------------------------------
+// org.mpris.MediaPlayer2.Player
+// ------------------------------------------------------------------------
+
+struct Player {
+    state: SharedState,
+    handler: RequestHandler,
+}
+
+impl Player {
+    fn state(&self) -> std::sync::MutexGuard<'_, PlayerState> {
+        self.state.lock().unwrap()
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn next(&self) {
+        (self.handler)(Request::Action("next_track"));
+    }
+
+    fn previous(&self) {
+        (self.handler)(Request::Action("previous_track"));
+    }
+
+    fn pause(&self) {
+        (self.handler)(Request::Action("pause"));
+    }
+
+    fn play_pause(&self) {
+        (self.handler)(Request::Action("play_pause"));
+    }
+
+    fn stop(&self) {
+        (self.handler)(Request::Action("stop"));
+    }
+
+    fn play(&self) {
+        (self.handler)(Request::Action("play"));
+    }
+
+    // Offset in microseconds
+    fn seek(&self, offset: i64) {
+        (self.handler)(Request::SeekBy(offset / 1000));
+    }
+
+    // Ignored unless `track_id` is the current track, as the spec requires
+    fn set_position(&self, track_id: ObjectPath<'_>, position: i64) {
+        if position < 0 || track_id.as_str() != self.state().track_path().as_str() {
+            return;
+        }
+        (self.handler)(Request::SetPosition(position as u64 / 1000));
+    }
+
+    fn open_uri(&self, _uri: &str) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported(
+            "Opening URIs is not supported".to_string(),
+        ))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        self.state().status.as_str()
+    }
+
+    #[zbus(property)]
+    fn loop_status(&self) -> &str {
+        self.state().loop_status.as_str()
+    }
+
+    #[zbus(property)]
+    fn set_loop_status(&self, value: &str) -> zbus::Result<()> {
+        let status = LoopStatus::parse(value)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown loop status '{}'", value)))?;
+        (self.handler)(Request::SetLoopStatus(status));
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        self.state().rate
+    }
+
+    // A rate of 0 means pause; others are clamped to the supported range
+    #[zbus(property)]
+    fn set_rate(&self, value: f64) {
+        if value == 0.0 {
+            (self.handler)(Request::Action("pause"));
+        } else if value.is_finite() {
+            (self.handler)(Request::SetRate(value.clamp(MINIMUM_RATE, MAXIMUM_RATE)));
+        }
+    }
+
+    #[zbus(property)]
+    fn shuffle(&self) -> bool {
+        self.state().shuffle
+    }
+
+    #[zbus(property)]
+    fn set_shuffle(&self, value: bool) {
+        (self.handler)(Request::SetShuffle(value));
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let state = self.state();
+        metadata_map(state.track_path(), state.metadata.as_ref())
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.state().volume
+    }
+
+    #[zbus(property)]
+    fn set_volume(&self, value: f64) {
+        if value.is_finite() {
+            (self.handler)(Request::SetVolume(value.clamp(0.0, 1.0)));
+        }
+    }
+
+    // Clients interpolate the position; jumps are announced with Seeked
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.state().position_ms as i64 * 1000
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn minimum_rate(&self) -> f64 {
+        MINIMUM_RATE
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn maximum_rate(&self) -> f64 {
+        MAXIMUM_RATE
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        self.state().can_go_next
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        self.state().can_go_previous
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        self.state().metadata.is_some()
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        self.state().metadata.is_some()
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        self.state()
+            .metadata
+            .as_ref()
+            .is_some_and(|m| m.length_ms.is_some())
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+// ------------------------------------------------------------------------
+// org.mpris.MediaPlayer2.TrackList
+// ------------------------------------------------------------------------
+
+struct TrackList {
+    tracks: SharedTracks,
+    handler: RequestHandler,
+}
+
+impl TrackList {
+    fn find(&self, track_id: &ObjectPath<'_>) -> Option<Metadata> {
+        let tracks = self.tracks.lock().unwrap();
+        tracks
+            .iter()
+            .find(|t| track_path(&t.track_id).as_str() == track_id.as_str())
+            .cloned()
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.TrackList")]
+impl TrackList {
+    // Unknown ids are skipped
+    fn get_tracks_metadata(
+        &self,
+        track_ids: Vec<ObjectPath<'_>>,
+    ) -> Vec<HashMap<String, OwnedValue>> {
+        track_ids
+            .iter()
+            .filter_map(|id| self.find(id))
+            .map(|t| metadata_map(track_path(&t.track_id), Some(&t)))
+            .collect()
+    }
+
+    // CanEditTracks is false, so these have no effect
+    fn add_track(&self, _uri: &str, _after_track: ObjectPath<'_>, _set_as_current: bool) {}
+
+    fn remove_track(&self, _track_id: ObjectPath<'_>) {}
+
+    fn go_to(&self, track_id: ObjectPath<'_>) {
+        if let Some(track) = self.find(&track_id) {
+            (self.handler)(Request::GoTo(track.track_id));
+        }
+    }
+
+    #[zbus(signal)]
+    async fn track_list_replaced(
+        emitter: &SignalEmitter<'_>,
+        tracks: Vec<OwnedObjectPath>,
+        current_track: OwnedObjectPath,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn track_added(
+        emitter: &SignalEmitter<'_>,
+        metadata: HashMap<String, OwnedValue>,
+        after_track: OwnedObjectPath,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn track_removed(
+        emitter: &SignalEmitter<'_>,
+        track_id: OwnedObjectPath,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn track_metadata_changed(
+        emitter: &SignalEmitter<'_>,
+        track_id: OwnedObjectPath,
+        metadata: HashMap<String, OwnedValue>,
+    ) -> zbus::Result<()>;
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn tracks(&self) -> Vec<OwnedObjectPath> {
+        let tracks = self.tracks.lock().unwrap();
+        tracks.iter().map(|t| track_path(&t.track_id)).collect()
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_edit_tracks(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TrackListChange {
+    Replaced,
+    // Index into the new list
+    Added(usize),
+    Removed(String),
+    MetadataChanged(usize),
+}
+
+// Describe the step from one queue to the next as TrackList signals. Pure
+// additions and removals are reported one by one; once the surviving
+// entries change order, or none survive, the whole list is replaced.
+fn tracklist_changes(before: &[Metadata], after: &[Metadata]) -> Vec<TrackListChange> {
+    if before == after {
+        return Vec::new();
+    }
+    let before_ids: HashSet<&str> = before.iter().map(|t| t.track_id.as_str()).collect();
+    let after_ids: HashSet<&str> = after.iter().map(|t| t.track_id.as_str()).collect();
+    let kept_before: Vec<&Metadata> = before
+        .iter()
+        .filter(|t| after_ids.contains(t.track_id.as_str()))
+        .collect();
+    let kept_after: Vec<&Metadata> = after
+        .iter()
+        .filter(|t| before_ids.contains(t.track_id.as_str()))
+        .collect();
+    let same_order = kept_before
+        .iter()
+        .zip(&kept_after)
+        .all(|(b, a)| b.track_id == a.track_id);
+    if kept_before.is_empty() || !same_order {
+        return vec![TrackListChange::Replaced];
+    }
+
+    let mut changes: Vec<TrackListChange> = before
+        .iter()
+        .filter(|t| !after_ids.contains(t.track_id.as_str()))
+        .map(|t| TrackListChange::Removed(t.track_id.clone()))
+        .collect();
+    for (index, track) in after.iter().enumerate() {
+        if !before_ids.contains(track.track_id.as_str()) {
+            changes.push(TrackListChange::Added(index));
+        }
+    }
+    for (b, a) in kept_before.iter().zip(&kept_after) {
+        if b != a {
+            let index = after.iter().position(|t| t.track_id == a.track_id);
+            changes.extend(index.map(TrackListChange::MetadataChanged));
+        }
+    }
+    changes
+}
+
+async fn emit_tracklist_changes(
+    conn: &zbus::Connection,
+    tracks: &[Metadata],
+    current: OwnedObjectPath,
+    changes: &[TrackListChange],
+) -> zbus::Result<()> {
+    let iface = conn
+        .object_server()
+        .interface::<_, TrackList>(OBJECT_PATH)
+        .await?;
+    let emitter = iface.signal_emitter();
+    for change in changes {
+        match change {
+            TrackListChange::Replaced => {
+                let paths = tracks.iter().map(|t| track_path(&t.track_id)).collect();
+                TrackList::track_list_replaced(emitter, paths, current.clone()).await?
+            }
+            TrackListChange::Added(index) => {
+                let track = &tracks[*index];
+                let after = match index.checked_sub(1) {
+                    Some(i) => track_path(&tracks[i].track_id),
+                    None => no_track(),
+                };
+                let metadata = metadata_map(track_path(&track.track_id), Some(track));
+                TrackList::track_added(emitter, metadata, after).await?
+            }
+            TrackListChange::Removed(track_id) => {
+                TrackList::track_removed(emitter, track_path(track_id)).await?
+            }
+            TrackListChange::MetadataChanged(index) => {
+                let track = &tracks[*index];
+                let path = track_path(&track.track_id);
+                let metadata = metadata_map(path.clone(), Some(track));
+                TrackList::track_metadata_changed(emitter, path, metadata).await?
+            }
+        }
+    }
+    iface.get().await.tracks_invalidate(emitter).await?;
+    Ok(())
+}
+
+// Player properties that emit PropertiesChanged
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Property {
+    PlaybackStatus,
+    LoopStatus,
+    Rate,
+    Shuffle,
+    Metadata,
+    Volume,
+    CanGoNext,
+    CanGoPrevious,
+    CanPlay,
+    CanPause,
+    CanSeek,
+}
+
+fn changed_properties(before: &PlayerState, after: &PlayerState) -> Vec<Property> {
+    let mut changed = Vec::new();
+    if before.status != after.status {
+        changed.push(Property::PlaybackStatus);
+    }
+    if before.loop_status != after.loop_status {
+        changed.push(Property::LoopStatus);
+    }
+    if before.rate != after.rate {
+        changed.push(Property::Rate);
+    }
+    if before.shuffle != after.shuffle {
+        changed.push(Property::Shuffle);
+    }
+    if before.metadata != after.metadata {
+        changed.push(Property::Metadata);
+    }
+    if before.volume != after.volume {
+        changed.push(Property::Volume);
+    }
+    if before.can_go_next != after.can_go_next {
+        changed.push(Property::CanGoNext);
+    }
+    if before.can_go_previous != after.can_go_previous {
+        changed.push(Property::CanGoPrevious);
+    }
+    if before.metadata.is_some() != after.metadata.is_some() {
+        changed.push(Property::CanPlay);
+        changed.push(Property::CanPause);
+    }
+    let seekable = |s: &PlayerState| s.metadata.as_ref().is_some_and(|m| m.length_ms.is_some());
+    if seekable(before) != seekable(after) {
+        changed.push(Property::CanSeek);
+    }
+    changed
+}
+
+async fn emit_changed(conn: &zbus::Connection, changed: &[Property]) -> zbus::Result<()> {
+    let iface = conn
+        .object_server()
+        .interface::<_, Player>(OBJECT_PATH)
+        .await?;
+    let emitter = iface.signal_emitter();
+    let player = iface.get().await;
+    for property in changed {
+        match property {
+            Property::PlaybackStatus => player.playback_status_changed(emitter).await?,
+            Property::LoopStatus => player.loop_status_changed(emitter).await?,
+            Property::Rate => player.rate_changed(emitter).await?,
+            Property::Shuffle => player.shuffle_changed(emitter).await?,
+            Property::Metadata => player.metadata_changed(emitter).await?,
+            Property::Volume => player.volume_changed(emitter).await?,
+            Property::CanGoNext => player.can_go_next_changed(emitter).await?,
+            Property::CanGoPrevious => player.can_go_previous_changed(emitter).await?,
+            Property::CanPlay => player.can_play_changed(emitter).await?,
+            Property::CanPause => player.can_pause_changed(emitter).await?,
+            Property::CanSeek => player.can_seek_changed(emitter).await?,
+        }
+    }
+    Ok(())
+}
+
+pub struct MprisServer {
+    conn: zbus::Connection,
+    state: SharedState,
+    tracks: SharedTracks,
+}
+
+impl MprisServer {
+    // Claim BUS_NAME on the session bus and export the interfaces
+    pub async fn start(handler: RequestHandler) -> zbus::Result<MprisServer> {
+        MprisServer::serve(zbus::connection::Builder::session()?, handler).await
+    }
+
+    async fn serve(
+        builder: zbus::connection::Builder<'_>,
+        handler: RequestHandler,
+    ) -> zbus::Result<MprisServer> {
+        let state = SharedState::default();
+        let tracks = SharedTracks::default();
+        let conn = builder
+            .name(BUS_NAME)?
+            .serve_at(
+                OBJECT_PATH,
+                Root {
+                    handler: handler.clone(),
+                },
+            )?
+            .serve_at(
+                OBJECT_PATH,
+                Player {
+                    state: state.clone(),
+                    handler: handler.clone(),
+                },
+            )?
+            .serve_at(
+                OBJECT_PATH,
+                TrackList {
+                    tracks: tracks.clone(),
+                    handler,
+                },
+            )?
+            .build()
+            .await?;
+        Ok(MprisServer {
+            conn,
+            state,
+            tracks,
+        })
+    }
+
+    // Apply a change reported by the webview. PropertiesChanged goes out in
+    // the background for whatever actually changed.
+    pub fn update(&self, f: impl FnOnce(&mut PlayerState)) {
+        let changed = {
+            let mut state = self.state.lock().unwrap();
+            let before = state.clone();
+            f(&mut state);
+            changed_properties(&before, &state)
+        };
+        if changed.is_empty() {
+            return;
+        }
+        let conn = self.conn.clone();
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    if let Err(e) = emit_changed(&conn, &changed).await {
+                        log::warn!("Failed to emit MPRIS PropertiesChanged: {}", e);
+                    }
+                },
+                "mpris-properties-changed",
+            )
+            .detach();
+    }
+
+    // Replace the play queue; the TrackList signals go out in the background
+    pub fn set_tracks(&self, tracks: Vec<Metadata>) {
+        let changes = {
+            let mut current = self.tracks.lock().unwrap();
+            let changes = tracklist_changes(&current, &tracks);
+            *current = tracks.clone();
+            changes
+        };
+        if changes.is_empty() {
+            return;
+        }
+        let current = self.state.lock().unwrap().track_path();
+        let conn = self.conn.clone();
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    if let Err(e) = emit_tracklist_changes(&conn, &tracks, current, &changes).await
+                    {
+                        log::warn!("Failed to emit MPRIS TrackList signals: {}", e);
+                    }
+                },
+                "mpris-tracklist-changed",
+            )
+            .detach();
+    }
+
+    // The position jumped (seek); emits Seeked
+    pub fn seeked(&self, position_ms: u64) {
+        self.state.lock().unwrap().position_ms = position_ms;
+        let conn = self.conn.clone();
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    let result = async {
+                        let iface = conn
+                            .object_server()
+                            .interface::<_, Player>(OBJECT_PATH)
+                            .await?;
+                        Player::seeked(iface.signal_emitter(), position_ms as i64 * 1000).await
+                    };
+                    if let Err(e) = result.await {
+                        log::warn!("Failed to emit MPRIS Seeked: {}", e);
+                    }
+                },
+                "mpris-seeked",
+            )
+            .detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::time::Duration;
+
+    fn track(id: &str, title: &str) -> Metadata {
+        Metadata {
+            track_id: id.to_string(),
+            title: title.to_string(),
+            ..Metadata::default()
+        }
+    }
+
+    #[test]
+    fn unchanged_state_changes_no_properties() {
+        let state = PlayerState::default();
+        assert_eq!(changed_properties(&state, &state.clone()), Vec::new());
+        // Position is announced with Seeked, not PropertiesChanged
+        let moved = PlayerState {
+            position_ms: 5000,
+            ..PlayerState::default()
+        };
+        assert_eq!(changed_properties(&state, &moved), Vec::new());
+    }
+
+    #[test]
+    fn changed_fields_map_to_properties() {
+        let before = PlayerState::default();
+        let after = PlayerState {
+            status: PlaybackStatus::Playing,
+            loop_status: LoopStatus::Playlist,
+            rate: 1.5,
+            shuffle: true,
+            volume: 0.5,
+            can_go_next: true,
+            can_go_previous: true,
+            ..PlayerState::default()
+        };
+        assert_eq!(
+            changed_properties(&before, &after),
+            vec![
+                Property::PlaybackStatus,
+                Property::LoopStatus,
+                Property::Rate,
+                Property::Shuffle,
+                Property::Volume,
+                Property::CanGoNext,
+                Property::CanGoPrevious,
+            ]
+        );
+    }
+
+    #[test]
+    fn loading_a_track_changes_the_capabilities() {
+        let empty = PlayerState::default();
+        let loaded = PlayerState {
+            metadata: Some(track("a", "One")),
+            ..PlayerState::default()
+        };
+        assert_eq!(
+            changed_properties(&empty, &loaded),
+            vec![Property::Metadata, Property::CanPlay, Property::CanPause]
+        );
+
+        // The length arriving later makes the track seekable
+        let mut seekable = loaded.clone();
+        seekable.metadata.as_mut().unwrap().length_ms = Some(60_000);
+        assert_eq!(
+            changed_properties(&loaded, &seekable),
+            vec![Property::Metadata, Property::CanSeek]
+        );
+        assert_eq!(
+            changed_properties(&seekable, &empty),
+            vec![
+                Property::Metadata,
+                Property::CanPlay,
+                Property::CanPause,
+                Property::CanSeek,
+            ]
+        );
+    }
+
+    #[test]
+    fn loop_status_maps_to_repeat_modes() {
+        for status in [LoopStatus::None, LoopStatus::Track, LoopStatus::Playlist] {
+            assert_eq!(LoopStatus::from_repeat_mode(status.repeat_mode()), status);
+            assert_eq!(LoopStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(LoopStatus::from_repeat_mode("RepeatOne"), LoopStatus::Track);
+        assert_eq!(LoopStatus::from_repeat_mode("RepeatAll"), LoopStatus::Playlist);
+        assert_eq!(LoopStatus::from_repeat_mode("RepeatNone"), LoopStatus::None);
+        assert_eq!(LoopStatus::from_repeat_mode(""), LoopStatus::None);
+        assert_eq!(LoopStatus::parse("none"), None);
+    }
+
+    fn queue(ids: &[&str]) -> Vec<Metadata> {
+        ids.iter().map(|id| track(id, id)).collect()
+    }
+
+    #[test]
+    fn identical_queues_have_no_changes() {
+        assert_eq!(tracklist_changes(&queue(&["a", "b"]), &queue(&["a", "b"])), Vec::new());
+        assert_eq!(tracklist_changes(&[], &[]), Vec::new());
+    }
+
+    #[test]
+    fn appended_tracks_are_added() {
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "b"]), &queue(&["a", "b", "c", "d"])),
+            vec![TrackListChange::Added(2), TrackListChange::Added(3)]
+        );
+        // Inserted in the middle, reported at its index in the new queue
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "c"]), &queue(&["a", "b", "c"])),
+            vec![TrackListChange::Added(1)]
+        );
+    }
+
+    #[test]
+    fn removed_tracks_are_removed() {
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "b", "c"]), &queue(&["a", "c"])),
+            vec![TrackListChange::Removed("b".to_string())]
+        );
+        // Removed and added in one step
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "b", "c"]), &queue(&["b", "c", "d"])),
+            vec![
+                TrackListChange::Removed("a".to_string()),
+                TrackListChange::Added(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn reordered_queue_is_replaced() {
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "b", "c"]), &queue(&["a", "c", "b"])),
+            vec![TrackListChange::Replaced]
+        );
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "b"]), &queue(&["b", "x", "a"])),
+            vec![TrackListChange::Replaced]
+        );
+    }
+
+    #[test]
+    fn disjoint_queue_is_replaced() {
+        assert_eq!(
+            tracklist_changes(&queue(&["a", "b"]), &queue(&["c", "d"])),
+            vec![TrackListChange::Replaced]
+        );
+        assert_eq!(
+            tracklist_changes(&[], &queue(&["a"])),
+            vec![TrackListChange::Replaced]
+        );
+        assert_eq!(
+            tracklist_changes(&queue(&["a"]), &[]),
+            vec![TrackListChange::Replaced]
+        );
+    }
+
+    #[test]
+    fn metadata_only_change() {
+        let before = queue(&["a", "b", "c"]);
+        let mut after = before.clone();
+        after[1].art_url = Some("file:///covers/b.jpg".to_string());
+        assert_eq!(
+            tracklist_changes(&before, &after),
+            vec![TrackListChange::MetadataChanged(1)]
+        );
+
+        // Reported at the index in the new queue
+        let mut after = queue(&["x", "a", "b", "c"]);
+        after[3].title = "Renamed".to_string();
+        assert_eq!(
+            tracklist_changes(&before, &after),
+            vec![
+                TrackListChange::Added(0),
+                TrackListChange::MetadataChanged(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn track_paths_are_valid_object_paths() {
+        let path = track_path("4f1c-9a_2");
+        assert!(path.as_str().starts_with("/org/jellyfin/desktop/track/4f1c_9a_2_"));
+        assert_eq!(track_path("4f1c-9a_2"), path);
+        assert!(ObjectPath::try_from(track_path("ü/ .").as_str()).is_ok());
+        assert_eq!(PlayerState::default().track_path().as_str(), NO_TRACK);
+    }
+
+    #[test]
+    fn track_paths_are_unique() {
+        let ids = ["a-b", "a_b", "a.b", "a b", "ab"];
+        let paths: HashSet<OwnedObjectPath> = ids.iter().map(|id| track_path(id)).collect();
+        assert_eq!(paths.len(), ids.len());
+    }
+
+    // ------------------------------------------------------------------
+    // Private bus
+    // ------------------------------------------------------------------
+
+    #[zbus::proxy(
+        interface = "org.mpris.MediaPlayer2.Player",
+        default_service = "org.mpris.MediaPlayer2.jellyfin_desktop",
+        default_path = "/org/mpris/MediaPlayer2"
+    )]
+    trait PlayerClient {
+        #[zbus(property)]
+        fn metadata(&self) -> zbus::Result<HashMap<String, OwnedValue>>;
+        #[zbus(property)]
+        fn rate(&self) -> zbus::Result<f64>;
+        #[zbus(property)]
+        fn set_rate(&self, value: f64) -> zbus::Result<()>;
+        #[zbus(property)]
+        fn loop_status(&self) -> zbus::Result<String>;
+        #[zbus(property)]
+        fn set_loop_status(&self, value: &str) -> zbus::Result<()>;
+        #[zbus(property)]
+        fn set_volume(&self, value: f64) -> zbus::Result<()>;
+        #[zbus(property)]
+        fn set_shuffle(&self, value: bool) -> zbus::Result<()>;
+    }
+
+    // A `dbus-daemon --session` of our own, stopped on drop
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        fn start() -> Option<PrivateBus> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--print-address", "--nofork"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            let stdout = daemon.stdout.take()?;
+            BufReader::new(stdout).read_line(&mut address).ok()?;
+            Some(PrivateBus {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    async fn exercise_server(address: &str) -> zbus::Result<Vec<Request>> {
+        let requests: Arc<Mutex<Vec<Request>>> = Arc::default();
+        let recorded = requests.clone();
+        let handler: RequestHandler = Arc::new(move |request| recorded.lock().unwrap().push(request));
+        let server =
+            MprisServer::serve(zbus::connection::Builder::address(address)?, handler).await?;
+
+        let client = zbus::connection::Builder::address(address)?.build().await?;
+        let properties = fdo::PropertiesProxy::builder(&client)
+            .destination(BUS_NAME)?
+            .path(OBJECT_PATH)?
+            .build()
+            .await?;
+        let mut signals = properties.receive_properties_changed().await?;
+        let player = PlayerClientProxy::builder(&client)
+            .cache_properties(zbus::proxy::CacheProperties::No)
+            .build()
+            .await?;
+
+        server.update(|state| {
+            state.metadata = Some(Metadata {
+                length_ms: Some(90_000),
+                ..track("item-1", "First")
+            });
+            state.rate = 1.5;
+            state.loop_status = LoopStatus::Playlist;
+        });
+
+        // Each changed property is announced in its own PropertiesChanged
+        let mut announced: HashSet<String> = HashSet::new();
+        while !["Metadata", "Rate", "LoopStatus"]
+            .iter()
+            .all(|name| announced.contains(*name))
+        {
+            let signal = signals.next().await.expect("PropertiesChanged stream ended");
+            let args = signal.args()?;
+            assert_eq!(args.interface_name().as_str(), "org.mpris.MediaPlayer2.Player");
+            announced.extend(args.changed_properties().keys().map(|k| k.to_string()));
+        }
+
+        let metadata = player.metadata().await?;
+        let title: String = metadata["xesam:title"].clone().try_into()?;
+        assert_eq!(title, "First");
+        let length: i64 = metadata["mpris:length"].clone().try_into()?;
+        assert_eq!(length, 90_000_000);
+        assert_eq!(player.rate().await?, 1.5);
+        assert_eq!(player.loop_status().await?, "Playlist");
+
+        player.set_volume(0.25).await?;
+        player.set_rate(1.25).await?;
+        player.set_shuffle(true).await?;
+        player.set_loop_status("Track").await?;
+        assert!(player.set_loop_status("Sometimes").await.is_err());
+
+        let requests = requests.lock().unwrap().clone();
+        Ok(requests)
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon on PATH; run with --ignored"]
+    fn properties_over_a_private_bus() {
+        let bus = PrivateBus::start().expect("could not start dbus-daemon");
+        let address = bus.address.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(zbus::block_on(exercise_server(&address)));
+        });
+        let requests = rx
+            .recv_timeout(Duration::from_secs(20))
+            .expect("timed out talking to the MPRIS server")
+            .expect("MPRIS round trip failed");
+        assert_eq!(
+            requests,
+            vec![
+                Request::SetVolume(0.25),
+                Request::SetRate(1.25),
+                Request::SetShuffle(true),
+                Request::SetLoopStatus(LoopStatus::Track),
+            ]
+        );
+    }
+}
@@ -0,0 +1,356 @@
+// ========================================================================
+// Admin Policy
+// ========================================================================
+//
+// Optional system-wide policy file for managed installs (shared living-room
+// machines, kiosks). It is read once at startup and never written by the app:
+//
+//   {
+//     "serverUrl": "https://jellyfin.lan:8096",
+//     "lockServerUrl": true,
+//     "kioskPin": "4711",
+//     "defaults": { "video": { "hardwareDecoding": "no" } },
+//     "locked": {
+//       "main": { "forceAlwaysFS": true, "checkForUpdates": false },
+//       "video": { "always_force_transcode": true }
+//     }
+//   }
+//
+// `defaults` replace schema defaults for keys the user never set. `locked`
+// values win over everything in settings.json and cannot be written.
+// `kioskPin` is required to exit or restart while kiosk mode is active.
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::settings;
+
+#[cfg(target_os = "windows")]
+fn policy_path() -> Option<PathBuf> {
+    std::env::var_os("ProgramData")
+        .map(|dir| PathBuf::from(dir).join("jellyfin-desktop").join("policy.json"))
+}
+
+#[cfg(target_os = "macos")]
+fn policy_path() -> Option<PathBuf> {
+    Some(PathBuf::from("/Library/Application Support/jellyfin-desktop/policy.json"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn policy_path() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc/jellyfin-desktop/policy.json"))
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct PolicyFile {
+    server_url: Option<String>,
+    lock_server_url: bool,
+    kiosk_pin: Option<String>,
+    defaults: BTreeMap<String, Map<String, Value>>,
+    locked: BTreeMap<String, Map<String, Value>>,
+}
+
+#[derive(Debug, Default)]
+pub struct Policy {
+    server_url: Option<String>,
+    lock_server_url: bool,
+    kiosk_pin: Option<String>,
+    // Keyed by settings store key (settings.<section>.<key>)
+    defaults: HashMap<String, Value>,
+    locked: HashMap<String, Value>,
+}
+
+impl Policy {
+    // A missing file means no policy. A malformed one is logged and ignored
+    // rather than preventing the app from starting.
+    pub fn load() -> Policy {
+        let Some(path) = policy_path() else {
+            return Policy::default();
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Policy::default(),
+            Err(e) => {
+                error!("Failed to read policy file {}: {}", path.display(), e);
+                return Policy::default();
+            }
+        };
+        let policy = match Policy::parse(&text) {
+            Ok(policy) => policy,
+            Err(e) => {
+                error!("Ignoring malformed policy file {}: {}", path.display(), e);
+                return Policy::default();
+            }
+        };
+        info!(
+            "Loaded policy from {}: {} defaults, {} locked settings, server URL {}",
+            path.display(),
+            policy.defaults.len(),
+            policy.locked.len(),
+            match (&policy.server_url, policy.locked_server_url()) {
+                (_, Some(_)) => "locked",
+                (Some(_), None) => "preset",
+                (None, None) => "not set",
+            }
+        );
+        policy
+    }
+
+    // The policy described by the file's JSON; invalid entries are dropped
+    fn parse(text: &str) -> serde_json::Result<Policy> {
+        let file: PolicyFile = serde_json::from_str(text)?;
+        let policy = Policy {
+            server_url: file
+                .server_url
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty()),
+            lock_server_url: file.lock_server_url,
+            kiosk_pin: file.kiosk_pin.filter(|pin| !pin.is_empty()),
+            defaults: validated_entries("defaults", file.defaults),
+            locked: validated_entries("locked", file.locked),
+        };
+        if policy.lock_server_url && policy.server_url.is_none() {
+            warn!("Policy sets lockServerUrl without a serverUrl; ignoring the lock");
+        }
+        Ok(policy)
+    }
+
+    pub fn locked_value(&self, section: &str, key: &str) -> Option<&Value> {
+        self.locked.get(&settings::store_key(section, key))
+    }
+
+    pub fn default_value(&self, section: &str, key: &str) -> Option<&Value> {
+        self.defaults.get(&settings::store_key(section, key))
+    }
+
+    pub fn is_locked(&self, section: &str, key: &str) -> bool {
+        self.locked_value(section, key).is_some()
+    }
+
+    // Locked keys of one section, for `settings_get_all`
+    pub fn locked_keys(&self, section: &str) -> Vec<String> {
+        let prefix = format!("settings.{}.", section);
+        let mut keys: Vec<String> = self
+            .locked
+            .keys()
+            .filter_map(|k| k.strip_prefix(&prefix).map(String::from))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn server_url(&self) -> Option<&str> {
+        self.server_url.as_deref()
+    }
+
+    pub fn locked_server_url(&self) -> Option<&str> {
+        self.server_url.as_deref().filter(|_| self.lock_server_url)
+    }
+
+    pub fn kiosk_pin(&self) -> Option<&str> {
+        self.kiosk_pin.as_deref()
+    }
+
+    // Compared without an early exit, so the time taken does not reveal how
+    // much of the PIN was right
+    pub fn kiosk_pin_matches(&self, pin: &str) -> bool {
+        let Some(expected) = self.kiosk_pin.as_deref() else {
+            return false;
+        };
+        let (expected, pin) = (expected.as_bytes(), pin.as_bytes());
+        let mut diff = expected.len() ^ pin.len();
+        for i in 0..expected.len().max(pin.len()) {
+            let a = expected.get(i).copied().unwrap_or(0);
+            let b = pin.get(i).copied().unwrap_or(0);
+            diff |= usize::from(a ^ b);
+        }
+        diff == 0
+    }
+
+    // Err when the server URL is locked to something other than `url`
+    pub fn check_server_url(&self, url: &str) -> Result<(), PolicyError> {
+        match self.locked_server_url() {
+            Some(locked) if locked != url.trim().trim_end_matches('/') => {
+                warn!("Rejected server URL {}: locked to {} by policy", url, locked);
+                Err(PolicyError::Locked("server_url".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Entries that fail schema validation are dropped so that a typo in the
+// policy cannot put an invalid value into effect.
+fn validated_entries(kind: &str, sections: BTreeMap<String, Map<String, Value>>) -> HashMap<String, Value> {
+    let mut entries = HashMap::new();
+    for (section, values) in sections {
+        for (key, value) in values {
+            if let Err(e) = settings::validate(&section, &key, &value) {
+                warn!("Ignoring policy {} entry: {}", kind, e);
+                continue;
+            }
+            entries.insert(settings::store_key(&section, &key), value);
+        }
+    }
+    entries
+}
+
+// Error for calls the webview is not allowed to make. Serialized as
+// { code, message } so callers can tell a policy lock (code "locked") or a
+// caller outside the trusted origins (code "forbidden") apart from an
+// ordinary rejection (code "rejected").
+#[derive(Debug)]
+pub enum PolicyError {
+    // Name of the locked entry, e.g. "main.forceAlwaysFS" or "server_url"
+    Locked(String),
+    // Name of the privileged command
+    Forbidden(String),
+    Rejected(String),
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::Locked(name) => write!(f, "'{}' is locked by administrator policy", name),
+            PolicyError::Forbidden(command) => write!(f, "'{}' is not allowed from this page", command),
+            PolicyError::Rejected(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<String> for PolicyError {
+    fn from(message: String) -> Self {
+        PolicyError::Rejected(message)
+    }
+}
+
+impl Serialize for PolicyError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let code = match self {
+            PolicyError::Locked(_) => "locked",
+            PolicyError::Forbidden(_) => "forbidden",
+            PolicyError::Rejected(_) => "rejected",
+        };
+        let mut state = serializer.serialize_struct("PolicyError", 2)?;
+        state.serialize_field("code", code)?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(value: Value) -> Policy {
+        Policy::parse(&value.to_string()).expect("valid policy JSON")
+    }
+
+    #[test]
+    fn defaults_and_locked_values_are_keyed_by_setting() {
+        let policy = policy(json!({
+            "defaults": { "video": { "hardwareDecoding": "no" } },
+            "locked": {
+                "main": { "forceAlwaysFS": true, "checkForUpdates": false },
+                "video": { "always_force_transcode": true },
+            },
+        }));
+        assert_eq!(policy.default_value("video", "hardwareDecoding"), Some(&json!("no")));
+        assert_eq!(policy.default_value("main", "forceAlwaysFS"), None);
+        assert_eq!(policy.locked_value("main", "checkForUpdates"), Some(&json!(false)));
+        assert!(policy.is_locked("video", "always_force_transcode"));
+        assert!(!policy.is_locked("video", "hardwareDecoding"));
+        assert_eq!(policy.locked_keys("main"), vec!["checkForUpdates", "forceAlwaysFS"]);
+        assert_eq!(policy.locked_keys("audio"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn invalid_entries_are_dropped() {
+        let policy = policy(json!({
+            "defaults": {
+                "video": { "hardwareDecoding": "turbo", "audio_delay_ms": 99_999 },
+                "nosuchsection": { "key": 1 },
+            },
+            "locked": {
+                "main": { "forceAlwaysFS": "yes", "noSuchKey": true, "checkForUpdates": false },
+            },
+        }));
+        assert!(policy.defaults.is_empty());
+        assert_eq!(policy.locked_keys("main"), vec!["checkForUpdates"]);
+    }
+
+    #[test]
+    fn empty_strings_count_as_unset() {
+        let policy = policy(json!({ "serverUrl": "  ", "kioskPin": "" }));
+        assert_eq!(policy.server_url(), None);
+        assert_eq!(policy.kiosk_pin(), None);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        assert!(Policy::parse("{ not json").is_err());
+        assert!(Policy::parse(r#"{ "lockServerUrl": "yes" }"#).is_err());
+        assert!(Policy::parse("{}").is_ok());
+    }
+
+    #[test]
+    fn locked_server_url_only_accepts_that_server() {
+        let policy = policy(json!({
+            "serverUrl": "https://jellyfin.lan:8096/",
+            "lockServerUrl": true,
+        }));
+        assert_eq!(policy.locked_server_url(), Some("https://jellyfin.lan:8096"));
+        assert!(policy.check_server_url("https://jellyfin.lan:8096").is_ok());
+        assert!(policy.check_server_url(" https://jellyfin.lan:8096/ ").is_ok());
+        assert!(matches!(
+            policy.check_server_url("https://evil.example"),
+            Err(PolicyError::Locked(name)) if name == "server_url"
+        ));
+    }
+
+    #[test]
+    fn unlocked_server_url_is_only_a_preset() {
+        let preset = policy(json!({ "serverUrl": "https://jellyfin.lan" }));
+        assert_eq!(preset.server_url(), Some("https://jellyfin.lan"));
+        assert_eq!(preset.locked_server_url(), None);
+        assert!(preset.check_server_url("https://other.lan").is_ok());
+
+        // A lock without a URL locks nothing
+        let lock_only = policy(json!({ "lockServerUrl": true }));
+        assert_eq!(lock_only.locked_server_url(), None);
+        assert!(lock_only.check_server_url("https://other.lan").is_ok());
+    }
+
+    #[test]
+    fn kiosk_pin_must_match_exactly() {
+        let policy = policy(json!({ "kioskPin": "4711" }));
+        assert!(policy.kiosk_pin_matches("4711"));
+        for wrong in ["", "4", "471", "4712", "47110", "4711\0", " 4711"] {
+            assert!(!policy.kiosk_pin_matches(wrong), "{:?}", wrong);
+        }
+        assert!(!Policy::default().kiosk_pin_matches(""));
+    }
+
+    #[test]
+    fn errors_serialize_with_a_code() {
+        let locked = serde_json::to_value(PolicyError::Locked("main.forceAlwaysFS".to_string())).unwrap();
+        assert_eq!(
+            locked,
+            json!({
+                "code": "locked",
+                "message": "'main.forceAlwaysFS' is locked by administrator policy",
+            })
+        );
+        let rejected = serde_json::to_value(PolicyError::from("nope".to_string())).unwrap();
+        assert_eq!(rejected, json!({ "code": "rejected", "message": "nope" }));
+    }
+}
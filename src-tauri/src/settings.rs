@@ -0,0 +1,797 @@
+// ========================================================================
+// Settings Schema
+// ========================================================================
+//
+// Authoritative description of every `settings.<section>.<key>` entry in
+// settings.json: type, default, display name and allowed values. The Rust
+// settings commands validate writes against this table, and the JS settings
+// modal renders from it via the `settings_schema` command.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SettingKind {
+    Bool,
+    Select {
+        options: &'static [&'static str],
+        // Options are extended at runtime (e.g. audio devices reported by mpv),
+        // so any non-empty string is accepted
+        extensible: bool,
+    },
+    Text,
+    // Hex colour as #RRGGBB or #AARRGGBB (mpv's sub-color format)
+    Color,
+    Number {
+        min: i64,
+        max: i64,
+    },
+    // Global key combination such as "Ctrl+Alt+Space" or "MediaPlayPause";
+    // blank = unbound
+    Shortcut,
+    // List of in-app key combinations (see keymap.rs); empty = unbound
+    Keys,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(untagged)]
+pub enum SettingDefault {
+    Bool(bool),
+    Str(&'static str),
+    Int(i64),
+    Keys(&'static [&'static str]),
+}
+
+impl SettingDefault {
+    pub fn to_value(self) -> Value {
+        match self {
+            SettingDefault::Bool(b) => Value::Bool(b),
+            SettingDefault::Str(s) => Value::String(s.to_string()),
+            SettingDefault::Int(i) => Value::from(i),
+            SettingDefault::Keys(keys) => Value::from(keys.to_vec()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingDescriptor {
+    pub key: &'static str,
+    pub name: &'static str,
+    #[serde(flatten)]
+    pub kind: SettingKind,
+    pub default: SettingDefault,
+    // Persisted by the player plugins but not shown in the settings modal
+    #[serde(skip_serializing_if = "is_false")]
+    pub hidden: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionSchema {
+    pub name: &'static str,
+    // Values in this section can be overridden per Jellyfin server
+    pub server_scoped: bool,
+    pub settings: &'static [SettingDescriptor],
+}
+
+const fn bool_setting(key: &'static str, name: &'static str, default: bool) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Bool,
+        default: SettingDefault::Bool(default),
+        hidden: false,
+    }
+}
+
+const fn select_setting(
+    key: &'static str,
+    name: &'static str,
+    default: &'static str,
+    options: &'static [&'static str],
+) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Select { options, extensible: false },
+        default: SettingDefault::Str(default),
+        hidden: false,
+    }
+}
+
+const fn text_setting(key: &'static str, name: &'static str, default: &'static str) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Text,
+        default: SettingDefault::Str(default),
+        hidden: false,
+    }
+}
+
+const fn color_setting(key: &'static str, name: &'static str, default: &'static str) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Color,
+        default: SettingDefault::Str(default),
+        hidden: false,
+    }
+}
+
+const fn number_setting(
+    key: &'static str,
+    name: &'static str,
+    default: i64,
+    min: i64,
+    max: i64,
+) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Number { min, max },
+        default: SettingDefault::Int(default),
+        hidden: false,
+    }
+}
+
+const fn shortcut_setting(key: &'static str, name: &'static str, default: &'static str) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Shortcut,
+        default: SettingDefault::Str(default),
+        hidden: false,
+    }
+}
+
+const fn keys_setting(key: &'static str, name: &'static str, default: &'static [&'static str]) -> SettingDescriptor {
+    SettingDescriptor {
+        key,
+        name,
+        kind: SettingKind::Keys,
+        default: SettingDefault::Keys(default),
+        hidden: false,
+    }
+}
+
+const fn hidden(mut desc: SettingDescriptor) -> SettingDescriptor {
+    desc.hidden = true;
+    desc
+}
+
+const fn extensible(mut desc: SettingDescriptor) -> SettingDescriptor {
+    if let SettingKind::Select { options, .. } = desc.kind {
+        desc.kind = SettingKind::Select { options, extensible: true };
+    }
+    desc
+}
+
+pub static SCHEMA: &[SectionSchema] = &[
+    SectionSchema {
+        name: "main",
+        server_scoped: false,
+        settings: &[
+            select_setting("layout", "Layout", "desktop", &["desktop", "tv"]),
+            // Deprecated: only the webview's copy of the window state, never
+            // read back. The saved state is per layout (state.layouts.<mode>.*).
+            hidden(bool_setting("fullscreen", "Fullscreen", false)),
+            hidden(bool_setting("alwaysOnTop", "Always on Top", false)),
+            bool_setting("forceAlwaysFS", "Kiosk Mode (prevent exiting fullscreen)", false),
+            bool_setting("allowBrowserZoom", "Allow Browser Zoom", false),
+            bool_setting("closeToTray", "Close to Tray", false),
+            // CSS selectors for the areas that move the window in cinema mode
+            text_setting("cinemaDragRegions", "Cinema Mode Drag Regions", ".skinHeader, .videoOsdBottom"),
+            // Monitor name from window_list_monitors; blank = the window's current monitor
+            text_setting("fullscreenMonitor", "Fullscreen Monitor", ""),
+            bool_setting("checkForUpdates", "Check for Updates", true),
+            // Covers shown by the OS media controls; 0 = don't cache, pass server URLs
+            number_setting("coverArtCacheMb", "Cover Art Cache Size (MB)", 100, 0, 2048),
+            // Comma-separated; other schemes need confirmation before opening
+            text_setting("externalUrlSchemes", "Link schemes opened without asking", "http,https,mailto"),
+        ],
+    },
+    SectionSchema {
+        name: "video",
+        server_scoped: true,
+        settings: &[
+            bool_setting("force_transcode_dovi", "Force transcode Dolby Vision", false),
+            bool_setting("force_transcode_hdr", "Force transcode HDR", false),
+            bool_setting("force_transcode_hi10p", "Force transcode 10-bit", false),
+            bool_setting("force_transcode_hevc", "Force transcode HEVC/H.265", false),
+            bool_setting("force_transcode_av1", "Force transcode AV1", false),
+            bool_setting("force_transcode_4k", "Force transcode 4K", false),
+            bool_setting("always_force_transcode", "Always force transcoding", false),
+            select_setting("max_audio_channels", "Max audio channels", "6", &["2", "6", "8"]),
+            select_setting(
+                "default_playback_speed",
+                "Default playback speed",
+                "1",
+                &["0.5", "0.75", "1", "1.25", "1.5", "1.75", "2"],
+            ),
+            select_setting(
+                "hardwareDecoding",
+                "Hardware Decoding",
+                "auto-safe",
+                &["auto-safe", "auto-copy", "no"],
+            ),
+            bool_setting("deinterlace", "Deinterlace", false),
+            bool_setting("refresh_rate_switching", "Match Display Refresh Rate to Video", false),
+            // Scale to fit the window to on each new video, see window_fit_to_video
+            select_setting("auto_fit_window", "Fit Window to Video", "off", &["off", "0.5", "1", "2", "fit"]),
+            bool_setting("lock_window_aspect", "Lock Window to Video Aspect Ratio", false),
+            select_setting(
+                "sync_mode",
+                "Video Sync Mode",
+                "audio",
+                &["audio", "display-resample", "display-adrop"],
+            ),
+            select_setting("cache_mb", "Cache Size (MB)", "150", &["10", "75", "150", "500"]),
+            number_setting("audio_delay_ms", "Audio Delay (ms)", 0, -10_000, 10_000),
+            bool_setting("allow_transcode_to_hevc", "Allow Transcode to HEVC", false),
+            hidden(select_setting(
+                "aspect",
+                "Aspect Ratio",
+                "auto",
+                &["auto", "cover", "fill", "4:3", "16:9", "21:9"],
+            )),
+        ],
+    },
+    SectionSchema {
+        name: "audio",
+        server_scoped: true,
+        settings: &[
+            extensible(select_setting("device", "Audio Device", "auto", &["auto"])),
+            select_setting("channels", "Audio Channels", "auto", &["auto", "2.0", "5.1", "7.1"]),
+            bool_setting("exclusive", "Exclusive Audio Mode (WASAPI)", false),
+            bool_setting("normalize", "Normalize Downmix Volume", false),
+            bool_setting("passthrough_ac3", "Passthrough AC3", false),
+            bool_setting("passthrough_dts", "Passthrough DTS", false),
+            bool_setting("passthrough_eac3", "Passthrough E-AC3", false),
+            bool_setting("passthrough_dtshd", "Passthrough DTS-HD", false),
+            bool_setting("passthrough_truehd", "Passthrough TrueHD", false),
+        ],
+    },
+    SectionSchema {
+        name: "subtitles",
+        server_scoped: true,
+        settings: &[
+            select_setting(
+                "size",
+                "Subtitle Size",
+                "32",
+                &["16", "20", "24", "28", "32", "36", "40", "48", "56", "64", "72"],
+            ),
+            text_setting("font", "Subtitle Font (blank = default)", ""),
+            color_setting("color", "Subtitle Color (hex ARGB)", "#FFFFFFFF"),
+            color_setting("border_color", "Border Color (hex ARGB)", "#FF000000"),
+            select_setting("border_size", "Border Size", "2", &["0", "1", "2", "3", "4", "5", "6"]),
+            color_setting("background_color", "Background Color (hex ARGB)", "#00000000"),
+            select_setting(
+                "ass_style_override",
+                "ASS Style Override",
+                "yes",
+                &["yes", "no", "force", "scale", "strip"],
+            ),
+            bool_setting("ass_scale_border", "Scale ASS Border & Shadow", true),
+        ],
+    },
+    SectionSchema {
+        // One key per in-app action; inputPlugin.js dispatches them
+        name: "input",
+        server_scoped: false,
+        settings: &[
+            keys_setting("play_pause", "Play/Pause", &["Space", "P", "MediaPlayPause"]),
+            keys_setting("stop", "Stop", &["X", "MediaStop"]),
+            keys_setting("next_track", "Next Track", &["MediaTrackNext"]),
+            keys_setting("previous_track", "Previous Track", &["MediaTrackPrevious"]),
+            keys_setting("seek_forward", "Seek Forward", &["PageDown"]),
+            keys_setting("seek_backward", "Seek Backward", &["PageUp"]),
+            keys_setting("step_forward", "Next Chapter", &["End"]),
+            keys_setting("step_backward", "Previous Chapter", &["Home"]),
+            keys_setting("cycle_audio", "Next Audio Track", &["A"]),
+            keys_setting("cycle_audio_back", "Previous Audio Track", &["Ctrl+A"]),
+            keys_setting("cycle_subtitles", "Next Subtitle Track", &["L"]),
+            keys_setting("cycle_subtitles_back", "Previous Subtitle Track", &["Ctrl+L"]),
+            keys_setting("toggle_subtitles", "Toggle Subtitles", &["S"]),
+            keys_setting("increase_volume", "Volume Up", &["Plus", "="]),
+            keys_setting("decrease_volume", "Volume Down", &["-"]),
+            keys_setting("cycle_aspect", "Cycle Aspect Ratio", &["Z"]),
+            keys_setting("fullscreen", "Toggle Fullscreen", &["F11", "Alt+Enter"]),
+            keys_setting("back", "Back", &["Escape", "Backspace"]),
+            keys_setting("enter", "Select", &["Enter"]),
+            keys_setting("toggle_debug", "Toggle Playback Stats", &["I"]),
+            keys_setting("close", "Quit", &["Ctrl+Q", "Ctrl+W"]),
+        ],
+    },
+    SectionSchema {
+        // Keys other than `enabled` are media-control-event actions
+        name: "hotkeys",
+        server_scoped: false,
+        settings: &[
+            bool_setting("enabled", "Global Media Hotkeys (work while unfocused)", false),
+            shortcut_setting("play_pause", "Play/Pause", "MediaPlayPause"),
+            shortcut_setting("next_track", "Next Track", "MediaTrackNext"),
+            shortcut_setting("previous_track", "Previous Track", "MediaTrackPrevious"),
+            shortcut_setting("stop", "Stop", "MediaStop"),
+            shortcut_setting("seek_forward", "Seek Forward", ""),
+            shortcut_setting("seek_backward", "Seek Backward", ""),
+        ],
+    },
+    SectionSchema {
+        // `<trigger>_<media>`, see autopause::setting_key
+        name: "autopause",
+        server_scoped: false,
+        settings: &[
+            bool_setting("minimize_video", "Pause Video When Minimized", true),
+            bool_setting("minimize_audio", "Pause Music When Minimized", false),
+            bool_setting("focus_video", "Pause Video When the Window Loses Focus", false),
+            bool_setting("focus_audio", "Pause Music When the Window Loses Focus", false),
+            bool_setting("lock_video", "Pause Video When the Screen Locks", true),
+            bool_setting("lock_audio", "Pause Music When the Screen Locks", false),
+        ],
+    },
+];
+
+pub fn store_key(section: &str, key: &str) -> String {
+    format!("settings.{}.{}", section, key)
+}
+
+pub fn server_store_key(server_id: &str, section: &str, key: &str) -> String {
+    format!("servers.{}.settings.{}.{}", server_id, section, key)
+}
+
+pub fn section(name: &str) -> Option<&'static SectionSchema> {
+    SCHEMA.iter().find(|s| s.name == name)
+}
+
+pub fn descriptor(section_name: &str, key: &str) -> Option<&'static SettingDescriptor> {
+    section(section_name)?.settings.iter().find(|d| d.key == key)
+}
+
+pub fn default_value(section_name: &str, key: &str) -> Option<Value> {
+    descriptor(section_name, key).map(|d| d.default.to_value())
+}
+
+pub fn section_defaults(section_name: &str) -> Map<String, Value> {
+    section(section_name)
+        .map(|s| {
+            s.settings
+                .iter()
+                .map(|d| (d.key.to_string(), d.default.to_value()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// ========================================================================
+// Validation
+// ========================================================================
+
+#[derive(Debug)]
+pub enum ValidationError {
+    UnknownSection(String),
+    UnknownKey { section: String, key: String },
+    InvalidValue { section: String, key: String, expected: String, got: Value },
+    NotServerScoped(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnknownSection(section) => {
+                write!(f, "Unknown settings section '{}'", section)
+            }
+            ValidationError::UnknownKey { section, key } => {
+                write!(f, "Unknown setting '{}.{}'", section, key)
+            }
+            ValidationError::InvalidValue { section, key, expected, got } => {
+                write!(f, "Invalid value for '{}.{}': expected {}, got {}", section, key, expected, got)
+            }
+            ValidationError::NotServerScoped(section) => {
+                write!(f, "Settings section '{}' cannot be overridden per server", section)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+pub fn validate(section_name: &str, key: &str, value: &Value) -> Result<(), ValidationError> {
+    let schema = section(section_name)
+        .ok_or_else(|| ValidationError::UnknownSection(section_name.to_string()))?;
+    let desc = schema
+        .settings
+        .iter()
+        .find(|d| d.key == key)
+        .ok_or_else(|| ValidationError::UnknownKey {
+            section: section_name.to_string(),
+            key: key.to_string(),
+        })?;
+
+    let invalid = |expected: String| ValidationError::InvalidValue {
+        section: section_name.to_string(),
+        key: key.to_string(),
+        expected,
+        got: value.clone(),
+    };
+
+    match desc.kind {
+        SettingKind::Bool => {
+            if !value.is_boolean() {
+                return Err(invalid("a boolean".to_string()));
+            }
+        }
+        SettingKind::Select { options, extensible } => {
+            // Numbers are accepted when they match an option's text
+            // (e.g. default_playback_speed written as 1 instead of "1")
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                _ => return Err(invalid(format!("one of {:?}", options))),
+            };
+            let ok = if extensible {
+                !text.is_empty()
+            } else {
+                options.contains(&text.as_str())
+            };
+            if !ok {
+                return Err(invalid(format!("one of {:?}", options)));
+            }
+        }
+        SettingKind::Text => {
+            if !value.is_string() {
+                return Err(invalid("a string".to_string()));
+            }
+        }
+        SettingKind::Color => {
+            if !value.as_str().is_some_and(is_hex_color) {
+                return Err(invalid("a hex colour (#RRGGBB or #AARRGGBB)".to_string()));
+            }
+        }
+        SettingKind::Number { min, max } => {
+            // Older builds persisted numeric settings as text inputs, so accept
+            // integer strings as well as numbers
+            let n = match value {
+                Value::Number(n) => n.as_i64(),
+                Value::String(s) => s.trim().parse::<i64>().ok(),
+                _ => None,
+            };
+            if !n.is_some_and(|n| (min..=max).contains(&n)) {
+                return Err(invalid(format!("an integer between {} and {}", min, max)));
+            }
+        }
+        SettingKind::Shortcut => {
+            let ok = value.as_str().is_some_and(|s| {
+                s.trim().is_empty() || s.parse::<tauri_plugin_global_shortcut::Shortcut>().is_ok()
+            });
+            if !ok {
+                return Err(invalid("a key combination such as Ctrl+Alt+Space, or blank".to_string()));
+            }
+        }
+        SettingKind::Keys => {
+            let keys = value
+                .as_array()
+                .and_then(|items| items.iter().map(Value::as_str).collect::<Option<Vec<&str>>>());
+            let Some(keys) = keys else {
+                return Err(invalid("a list of key combinations".to_string()));
+            };
+            if let Err(reason) = crate::keymap::parse_keys(keys) {
+                return Err(invalid(format!("a list of key combinations ({})", reason)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_hex_color(s: &str) -> bool {
+    match s.strip_prefix('#') {
+        Some(hex) => (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
+// ========================================================================
+// Scopes
+// ========================================================================
+//
+// Settings in server-scoped sections can be overridden per Jellyfin server,
+// keyed by the server Id from /System/Info/Public. Resolution order is
+// server override → global value → schema default.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsScope {
+    #[default]
+    Global,
+    // The active server
+    Server,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingSource {
+    Server,
+    Global,
+    Default,
+    // Locked by the admin policy (see policy.rs)
+    Policy,
+}
+
+// Split a raw store key into (server Id, section, key). Returns None for
+// entries that are not settings (server_url, state.geometry.*, ...).
+pub fn parse_store_key(store_key: &str) -> Option<(Option<&str>, &str, &str)> {
+    if let Some(rest) = store_key.strip_prefix("settings.") {
+        let (section, key) = rest.split_once('.')?;
+        return Some((None, section, key));
+    }
+    let rest = store_key.strip_prefix("servers.")?;
+    let (server_id, rest) = rest.split_once(".settings.")?;
+    let (section, key) = rest.split_once('.')?;
+    Some((Some(server_id), section, key))
+}
+
+pub fn is_server_scoped(section_name: &str) -> bool {
+    section(section_name).is_some_and(|s| s.server_scoped)
+}
+
+pub fn check_scope(section_name: &str, scope: SettingsScope) -> Result<(), ValidationError> {
+    if scope == SettingsScope::Server && !is_server_scoped(section_name) {
+        return Err(ValidationError::NotServerScoped(section_name.to_string()));
+    }
+    Ok(())
+}
+
+// Look up the effective value of a setting. `get` reads a raw store key.
+pub fn resolve(
+    section_name: &str,
+    key: &str,
+    server_id: Option<&str>,
+    get: impl Fn(&str) -> Option<Value>,
+) -> Option<(Value, SettingSource)> {
+    if let Some(id) = server_id.filter(|_| is_server_scoped(section_name)) {
+        if let Some(v) = get(&server_store_key(id, section_name, key)) {
+            return Some((v, SettingSource::Server));
+        }
+    }
+    if let Some(v) = get(&store_key(section_name, key)) {
+        return Some((v, SettingSource::Global));
+    }
+    default_value(section_name, key).map(|v| (v, SettingSource::Default))
+}
+
+// ========================================================================
+// Schema Versioning & Migrations
+// ========================================================================
+//
+// settings.json carries a top-level `schema_version`. Stores written before
+// versioning existed are treated as version 0. Each migration upgrades the
+// flat store map by exactly one version and must never fail: values it does
+// not recognise are left alone for validation to report.
+
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+pub const SCHEMA_VERSION: u64 = 4;
+
+pub struct Migration {
+    pub to_version: u64,
+    pub description: &'static str,
+    pub apply: fn(&mut Map<String, Value>),
+}
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        to_version: 1,
+        description: "rename video.hardware_decoding to video.hardwareDecoding",
+        apply: migrate_hardware_decoding_key,
+    },
+    Migration {
+        to_version: 2,
+        description: "normalize legacy value types (audio_delay_ms, default_playback_speed)",
+        apply: migrate_legacy_value_types,
+    },
+    Migration {
+        to_version: 3,
+        description: "store window position relative to its monitor",
+        apply: migrate_window_geometry,
+    },
+    Migration {
+        to_version: 4,
+        description: "move main.fullscreen and main.alwaysOnTop to the desktop layout state",
+        apply: migrate_layout_window_state,
+    },
+];
+
+pub fn pending_migrations(from_version: u64) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS.iter().filter(move |m| m.to_version > from_version)
+}
+
+fn migrate_hardware_decoding_key(entries: &mut Map<String, Value>) {
+    let old_key = store_key("video", "hardware_decoding");
+    let new_key = store_key("video", "hardwareDecoding");
+    if let Some(value) = entries.remove(&old_key) {
+        // A value under the new name wins — it was written by a newer build
+        entries.entry(new_key).or_insert(value);
+    }
+}
+
+fn migrate_legacy_value_types(entries: &mut Map<String, Value>) {
+    // audio_delay_ms used to be a free-text input and was stored as a string
+    let delay_key = store_key("video", "audio_delay_ms");
+    if let Some(Value::String(s)) = entries.get(&delay_key) {
+        if let Ok(ms) = s.trim().parse::<i64>() {
+            entries.insert(delay_key, Value::from(ms));
+        }
+    }
+
+    // default_playback_speed was seeded as a number but the select stores strings
+    let speed_key = store_key("video", "default_playback_speed");
+    if let Some(Value::Number(n)) = entries.get(&speed_key) {
+        let text = n.to_string();
+        entries.insert(speed_key, Value::String(text));
+    }
+}
+
+// state.geometry.x/y/w/h used to be physical pixels with absolute
+// coordinates; they are now logical pixels, with x/y offsets from the monitor
+// named in state.geometry.monitor. No scale factor was stored with them, so
+// neither the position nor the size can be converted. All four are dropped,
+// and the window opens once at the default size, centred.
+fn migrate_window_geometry(entries: &mut Map<String, Value>) {
+    for key in ["x", "y", "w", "h"] {
+        entries.remove(&format!("state.geometry.{}", key));
+    }
+}
+
+// The desktop window used to follow main.fullscreen and main.alwaysOnTop;
+// each layout now keeps its own flags under state.layouts.<mode>.
+fn migrate_layout_window_state(entries: &mut Map<String, Value>) {
+    for key in ["fullscreen", "alwaysOnTop"] {
+        if let Some(value) = entries.remove(&store_key("main", key)) {
+            // Per-layout state already saved by a newer build wins
+            entries.entry(format!("state.layouts.desktop.{}", key)).or_insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entries(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn hardware_decoding_key_is_renamed() {
+        let mut map = entries(json!({ "settings.video.hardware_decoding": "no" }));
+        migrate_hardware_decoding_key(&mut map);
+        assert_eq!(map, entries(json!({ "settings.video.hardwareDecoding": "no" })));
+    }
+
+    #[test]
+    fn hardware_decoding_key_keeps_newer_value() {
+        let mut map = entries(json!({
+            "settings.video.hardware_decoding": "no",
+            "settings.video.hardwareDecoding": "auto-copy",
+        }));
+        migrate_hardware_decoding_key(&mut map);
+        assert_eq!(map, entries(json!({ "settings.video.hardwareDecoding": "auto-copy" })));
+
+        // Running it again changes nothing
+        migrate_hardware_decoding_key(&mut map);
+        assert_eq!(map, entries(json!({ "settings.video.hardwareDecoding": "auto-copy" })));
+    }
+
+    #[test]
+    fn legacy_value_types_are_normalized() {
+        let mut map = entries(json!({
+            "settings.video.audio_delay_ms": " 150 ",
+            "settings.video.default_playback_speed": 1.5,
+        }));
+        migrate_legacy_value_types(&mut map);
+        let expected = entries(json!({
+            "settings.video.audio_delay_ms": 150,
+            "settings.video.default_playback_speed": "1.5",
+        }));
+        assert_eq!(map, expected);
+
+        migrate_legacy_value_types(&mut map);
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn unparseable_audio_delay_is_left_for_validation() {
+        let mut map = entries(json!({ "settings.video.audio_delay_ms": "soon" }));
+        migrate_legacy_value_types(&mut map);
+        assert_eq!(map, entries(json!({ "settings.video.audio_delay_ms": "soon" })));
+    }
+
+    #[test]
+    fn window_geometry_drops_physical_position_and_size() {
+        let mut map = entries(json!({
+            "state.geometry.x": 1920,
+            "state.geometry.y": 40,
+            "state.geometry.w": 1280,
+            "state.geometry.h": 720,
+            "state.layouts.tv.geometry.w": 1920,
+        }));
+        migrate_window_geometry(&mut map);
+        let expected = entries(json!({ "state.layouts.tv.geometry.w": 1920 }));
+        assert_eq!(map, expected);
+
+        migrate_window_geometry(&mut map);
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn layout_window_state_moves_to_desktop_layout() {
+        let mut map = entries(json!({
+            "settings.main.fullscreen": true,
+            "settings.main.alwaysOnTop": true,
+            "state.layouts.desktop.alwaysOnTop": false,
+            "state.layouts.tv.fullscreen": false,
+        }));
+        migrate_layout_window_state(&mut map);
+        let expected = entries(json!({
+            "state.layouts.desktop.fullscreen": true,
+            "state.layouts.desktop.alwaysOnTop": false,
+            "state.layouts.tv.fullscreen": false,
+        }));
+        assert_eq!(map, expected);
+
+        migrate_layout_window_state(&mut map);
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn migrations_step_one_version_at_a_time() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.to_version, index as u64 + 1);
+        }
+        assert_eq!(MIGRATIONS.last().map(|m| m.to_version), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn pending_migrations_start_after_stored_version() {
+        for from in 0..=SCHEMA_VERSION {
+            let versions: Vec<u64> = pending_migrations(from).map(|m| m.to_version).collect();
+            let expected: Vec<u64> = (from + 1..=SCHEMA_VERSION).collect();
+            assert_eq!(versions, expected, "from version {}", from);
+        }
+        // A store written by a newer build is left alone
+        assert_eq!(pending_migrations(SCHEMA_VERSION + 1).count(), 0);
+    }
+
+    #[test]
+    fn all_migrations_upgrade_a_version_zero_store() {
+        let mut map = entries(json!({
+            "settings.video.hardware_decoding": "no",
+            "settings.video.audio_delay_ms": "-40",
+            "state.geometry.x": 10,
+            "state.geometry.w": 800,
+            "settings.main.fullscreen": true,
+        }));
+        for migration in pending_migrations(0) {
+            (migration.apply)(&mut map);
+        }
+        assert_eq!(
+            map,
+            entries(json!({
+                "settings.video.hardwareDecoding": "no",
+                "settings.video.audio_delay_ms": -40,
+                "state.layouts.desktop.fullscreen": true,
+            }))
+        );
+    }
+}
@@ -49,7 +49,8 @@ document.addEventListener("DOMContentLoaded", async () => {
         await invoke("navigate_to_server", { url });
       }, 500);
     } catch (err: any) {
-      statusMsg.textContent = `Failed: ${err}`;
+      // Policy-checked commands reject with { code, message }
+      statusMsg.textContent = `Failed: ${err?.message ?? err}`;
       statusMsg.className = "status error";
       connectBtn.disabled = false;
     }